@@ -0,0 +1,359 @@
+//! Lookups from objects to the assets they belong to, built lazily and shared across scans.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use anyhow::{Context as _, Result};
+use jaq_json::Val;
+use jaq_std::ValT as _;
+use rabex::objects::pptr::{FileId, PathId};
+use rabex::objects::{ClassId, PPtr};
+use rabex::typetree::TypeTreeProvider;
+use rabex_env::handle::SerializedFileHandle;
+use rabex_env::resolver::EnvResolver;
+use tracing::warn;
+
+use crate::catalog::{AddressableAsset, Catalog};
+use crate::hierarchy::{field, local_path_id};
+
+/// The asset paths of a serialized file's objects, from the `m_Container` of its AssetBundle or
+/// of the ResourceManager.
+#[derive(Debug, Default, Clone)]
+pub struct Container {
+    /// Main assets and every object in their preload range.
+    pub by_object: HashMap<PathId, String>,
+    /// The main object of each asset path, by the lowercased path. See [`Container::main_object`].
+    pub by_path: HashMap<String, PathId>,
+}
+
+impl Container {
+    /// The main object of the asset at `asset_path`. Containers store their paths lowercased
+    /// while catalogs keep the project's casing, so paths are compared case insensitively.
+    pub fn main_object(&self, asset_path: &str) -> Option<PathId> {
+        self.by_path.get(&asset_path.to_lowercase()).copied()
+    }
+
+    fn add_main_object(&mut self, asset_path: &str, path_id: PathId) {
+        self.by_path
+            .entry(asset_path.to_lowercase())
+            .or_insert(path_id);
+    }
+}
+
+#[derive(Default)]
+pub struct AssetIndex {
+    catalog_path: Option<PathBuf>,
+    addressables: OnceLock<Option<Addressables>>,
+    /// Keyed by the path passed to `load_serialized`.
+    containers: Mutex<HashMap<String, Arc<Container>>>,
+    /// The ResourceManager's entries, by the file they point into.
+    resources: OnceLock<HashMap<String, Container>>,
+}
+
+struct Addressables {
+    assets: Vec<AddressableAsset>,
+    /// `(bundle, lowercased asset path)` to index into `assets`.
+    by_asset: HashMap<(String, String), usize>,
+    by_address: HashMap<String, usize>,
+    /// Bundle file name to the name of the scene it contains.
+    scene_bundles: HashMap<String, String>,
+}
+
+impl AssetIndex {
+    /// An index reading the addressables catalog at `catalog_path`, if any.
+    pub fn new(catalog_path: Option<PathBuf>) -> Self {
+        AssetIndex {
+            catalog_path,
+            ..Default::default()
+        }
+    }
+
+    /// An index for the game whose data folder is found from `game_dir`.
+    pub fn for_game(game_dir: &Path) -> Self {
+        let (_, data) = crate::info::game_dirs(game_dir);
+        let catalog = data.join("StreamingAssets/aa/catalog.json");
+        AssetIndex::new(catalog.exists().then_some(catalog))
+    }
+
+    fn addressables(&self) -> Option<&Addressables> {
+        self.addressables
+            .get_or_init(|| {
+                let path = self.catalog_path.as_ref()?;
+                let catalog = std::fs::read(path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| Catalog::from_json(&json))
+                    .with_context(|| format!("Could not read catalog {}", path.display()));
+                match catalog {
+                    Ok(catalog) => Some(Addressables::new(catalog.assets())),
+                    Err(e) => {
+                        warn!("{e:?}");
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    /// The catalog entry of the asset at `asset_path` in the bundle named `bundle`, with the path
+    /// compared case insensitively like [`Container::main_object`] does.
+    pub fn addressable_asset(&self, bundle: &str, asset_path: &str) -> Option<&AddressableAsset> {
+        let addressables = self.addressables()?;
+        let index = addressables
+            .by_asset
+            .get(&(bundle.to_owned(), asset_path.to_lowercase()))?;
+        Some(&addressables.assets[*index])
+    }
+
+    /// The addressable scene stored in the bundle named `bundle`.
+    pub fn bundle_scene(&self, bundle: &str) -> Option<&str> {
+        let addressables = self.addressables()?;
+        addressables.scene_bundles.get(bundle).map(String::as_str)
+    }
+
+    pub fn find_address(&self, address: &str) -> Option<&AddressableAsset> {
+        let addressables = self.addressables()?;
+        let index = addressables.by_address.get(address)?;
+        Some(&addressables.assets[*index])
+    }
+
+    /// The container of the file loaded from `path`, read on first use. A bundle whose
+    /// AssetBundle can't be read has an empty container.
+    pub fn container<R: EnvResolver, P: TypeTreeProvider>(
+        &self,
+        file: &SerializedFileHandle<'_, R, P>,
+        path: &str,
+    ) -> Arc<Container> {
+        let containers = self
+            .containers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(container) = containers.get(path) {
+            return Arc::clone(container);
+        }
+        // Reading happens unlocked, at worst two threads build the same container
+        drop(containers);
+
+        let mut container = read_container(file).unwrap_or_else(|e| {
+            warn!("Could not read the container of '{path}': {e:?}");
+            Container::default()
+        });
+        if let Some(resources) = self.resources(file).get(path) {
+            container.by_path.extend(resources.by_path.clone());
+            container.by_object.extend(resources.by_object.clone());
+        }
+        let container = Arc::new(container);
+        self.containers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(path.to_owned(), Arc::clone(&container));
+        container
+    }
+
+    /// The asset path of the object `path_id` in the file loaded from `path`, if it is a main
+    /// asset or preloaded by one.
+    pub fn asset_path<R: EnvResolver, P: TypeTreeProvider>(
+        &self,
+        file: &SerializedFileHandle<'_, R, P>,
+        path: &str,
+        path_id: PathId,
+    ) -> Option<String> {
+        self.container(file, path).by_object.get(&path_id).cloned()
+    }
+
+    fn resources<R: EnvResolver, P: TypeTreeProvider>(
+        &self,
+        file: &SerializedFileHandle<'_, R, P>,
+    ) -> &HashMap<String, Container> {
+        self.resources.get_or_init(|| {
+            let resources = file
+                .env
+                .load_serialized("globalgamemanagers")
+                .context("Could not load 'globalgamemanagers'")
+                .and_then(|globalgamemanagers| read_resources(&globalgamemanagers));
+            resources.unwrap_or_else(|e| {
+                warn!("Could not read the ResourceManager: {e:?}");
+                HashMap::new()
+            })
+        })
+    }
+}
+
+impl Addressables {
+    fn new(assets: Vec<AddressableAsset>) -> Self {
+        let mut by_asset = HashMap::new();
+        let mut by_address = HashMap::new();
+        let mut scene_bundles = HashMap::new();
+        for (i, asset) in assets.iter().enumerate() {
+            by_asset.insert((asset.bundle.clone(), asset.asset_path.to_lowercase()), i);
+            by_address.entry(asset.address.clone()).or_insert(i);
+            if let Some(scene) = crate::catalog::scene_name(&asset.asset_path) {
+                scene_bundles.insert(asset.bundle.clone(), scene.to_owned());
+            }
+        }
+        Addressables {
+            assets,
+            by_asset,
+            by_address,
+            scene_bundles,
+        }
+    }
+}
+
+/// Read the `m_Container` of the file's AssetBundle object. Files without one have an empty
+/// container.
+fn read_container<R: EnvResolver, P: TypeTreeProvider>(
+    file: &SerializedFileHandle<'_, R, P>,
+) -> Result<Container> {
+    let mut container = Container::default();
+    let Some(info) = file
+        .file
+        .objects()
+        .find(|info| info.m_ClassID == ClassId::AssetBundle)
+    else {
+        return Ok(container);
+    };
+    let bundle = file
+        .deref(PPtr::local(info.m_PathID).typed::<Val>())?
+        .read()
+        .context("Failed to read AssetBundle")?;
+
+    let preload_table: Vec<Option<PathId>> = match field(&bundle, "m_PreloadTable") {
+        Some(Val::Arr(pptrs)) => pptrs.iter().map(local_path_id).collect(),
+        _ => Vec::new(),
+    };
+
+    for (asset_path, asset_info) in map_entries(field(&bundle, "m_Container")) {
+        let Some(asset) = field(asset_info, "asset").and_then(local_path_id) else {
+            continue;
+        };
+        container.add_main_object(&asset_path, asset);
+        container.by_object.insert(asset, asset_path.clone());
+
+        let index = field(asset_info, "preloadIndex").and_then(|v| v.as_isize());
+        let size = field(asset_info, "preloadSize").and_then(|v| v.as_isize());
+        if let (Some(index), Some(size)) = (index, size) {
+            let range = index.max(0) as usize..(index + size).max(0) as usize;
+            for path_id in preload_table.get(range).into_iter().flatten().flatten() {
+                // The main asset of another path takes precedence
+                container
+                    .by_object
+                    .entry(*path_id)
+                    .or_insert_with(|| asset_path.clone());
+            }
+        }
+    }
+    Ok(container)
+}
+
+/// Read the `m_Container` of the ResourceManager in `globalgamemanagers`, whose PPtrs point into
+/// `resources.assets` and similar files.
+fn read_resources<R: EnvResolver, P: TypeTreeProvider>(
+    globalgamemanagers: &SerializedFileHandle<'_, R, P>,
+) -> Result<HashMap<String, Container>> {
+    let mut containers = HashMap::<String, Container>::new();
+    let Some(info) = globalgamemanagers
+        .file
+        .objects()
+        .find(|info| info.m_ClassID == ClassId::ResourceManager)
+    else {
+        return Ok(containers);
+    };
+    let resource_manager = globalgamemanagers
+        .deref(PPtr::local(info.m_PathID).typed::<Val>())?
+        .read()
+        .context("Failed to read ResourceManager")?;
+
+    for (asset_path, pptr) in map_entries(field(&resource_manager, "m_Container")) {
+        let file_id = field(pptr, "m_FileID").and_then(|v| v.as_isize());
+        let path_id = field(pptr, "m_PathID").and_then(|v| v.as_isize());
+        let (Some(file_id), Some(path_id)) = (file_id, path_id) else {
+            continue;
+        };
+        let Some(pptr) = PPtr::new(FileId::new(file_id as i32), path_id as PathId).optional()
+        else {
+            continue;
+        };
+        let file = match pptr.is_local() {
+            true => "globalgamemanagers".to_owned(),
+            false => match pptr.file_identifier(globalgamemanagers.file) {
+                Some(external) => external.pathName.clone(),
+                None => continue,
+            },
+        };
+
+        let container = containers.entry(file).or_default();
+        container.add_main_object(&asset_path, pptr.m_PathID);
+        container.by_object.insert(pptr.m_PathID, asset_path);
+    }
+    Ok(containers)
+}
+
+/// Entries of a serialized `map`, which is read as a list of `[key, value]` pairs or of
+/// `{first, second}` objects.
+pub(crate) fn map_entries(map: Option<&Val>) -> Vec<(String, &Val)> {
+    let Some(Val::Arr(entries)) = map else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let (key, value) = match entry {
+                Val::Arr(pair) if pair.len() == 2 => (&pair[0], &pair[1]),
+                _ => (field(entry, "first")?, field(entry, "second")?),
+            };
+            Some((
+                String::from_utf8_lossy(key.as_utf8_bytes()?).into_owned(),
+                value,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Addressables, AssetIndex, Container, map_entries};
+    use crate::catalog::AddressableAsset;
+    use jaq_json::Val;
+    use std::sync::OnceLock;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn map_entries_accepts_pairs_and_first_second() {
+        let pairs = val(r#"[["a", 1], ["b", 2]]"#);
+        let objects = val(r#"[{"first": "a", "second": 1}, {"first": "b", "second": 2}]"#);
+        for map in [pairs, objects] {
+            let entries = map_entries(Some(&map));
+            assert_eq!(entries, [("a".into(), &val("1")), ("b".into(), &val("2"))]);
+        }
+        assert!(map_entries(None).is_empty());
+    }
+
+    #[test]
+    fn asset_paths_are_compared_case_insensitively() {
+        // Containers store lowercased paths, catalogs the project's casing
+        let mut container = Container::default();
+        container.add_main_object("assets/prefabs/enemy.prefab", 7);
+        assert_eq!(
+            container.main_object("Assets/Prefabs/Enemy.prefab"),
+            Some(7)
+        );
+
+        let asset = AddressableAsset {
+            address: "enemy".into(),
+            asset_path: "Assets/Prefabs/Enemy.prefab".into(),
+            labels: Vec::new(),
+            bundle: "enemies.bundle".into(),
+            bundle_deps: Vec::new(),
+        };
+        let index = AssetIndex {
+            addressables: OnceLock::from(Some(Addressables::new(vec![asset]))),
+            ..Default::default()
+        };
+        let found = index.addressable_asset("enemies.bundle", "assets/prefabs/enemy.prefab");
+        assert_eq!(found.map(|asset| asset.address.as_str()), Some("enemy"));
+    }
+}
@@ -3,7 +3,8 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::path::Path;
 use std::time::Instant;
 use std::usize;
-use uniscan::{ScriptFilter, UniScan};
+use uniscan::inheritance::EnumNames;
+use uniscan::{FileFilter, ScriptFilter, UniScan};
 
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
@@ -23,17 +24,54 @@ fn init_tracing() {
 fn main() -> Result<()> {
     init_tracing();
 
-    let mut args = std::env::args().skip(1);
+    let mut args = std::env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("schema") => schema(args.skip(1)),
+        Some("info") => info(args.skip(1)),
+        Some("fsm") => fsm(args.skip(1)),
+        _ => scan(args),
+    }
+}
+
+/// `uniscan <game> <Script> [query] [--bundles=<path>]... [--files=<glob>] [--scene=<name>]...
+/// [--only-addressables] [--exclude-bundles] [--enum-names[=replace]]`
+fn scan(args: impl Iterator<Item = String>) -> Result<()> {
+    let (flags, args): (Vec<_>, Vec<_>) = args.partition(|arg| arg.starts_with("--"));
+    let mut args = args.into_iter();
     let game_dir = args.next().context("missing path to game")?;
     let script_filter = args.next().context("missing name of Script")?;
     let filter = args.next();
 
     let start = Instant::now();
 
-    let script_filter = ScriptFilter::new(&script_filter);
-    let uniscan = UniScan::new(Path::new(&game_dir), filter.as_deref().unwrap_or("."))?;
+    let script_filter = ScriptFilter::try_new(&script_filter)?;
+    let mut uniscan = UniScan::new(Path::new(&game_dir), filter.as_deref().unwrap_or("."))?;
+    let mut file_filter = FileFilter::default();
+    for flag in &flags {
+        if let Some(path) = flag.strip_prefix("--bundles=") {
+            uniscan.add_extra_files(Path::new(path))?;
+        } else if let Some(glob) = flag.strip_prefix("--files=") {
+            file_filter.glob = glob.to_owned();
+        } else if let Some(scene) = flag.strip_prefix("--scene=") {
+            file_filter.scenes.push(scene.to_owned());
+        } else if flag == "--only-addressables" {
+            file_filter.only_addressables = true;
+        } else if flag == "--exclude-bundles" {
+            file_filter.exclude_bundles = true;
+        } else if flag == "--enum-names" {
+            uniscan.enum_names = EnumNames::Sibling;
+        } else if flag == "--enum-names=replace" {
+            uniscan.enum_names = EnumNames::Replace;
+        } else if flag == "--base-types" {
+            uniscan.with_base_types = true;
+        } else {
+            anyhow::bail!("unknown flag '{flag}'");
+        }
+    }
 
-    let scan = uniscan.scan_all(&script_filter, usize::MAX)?;
+    let files = uniscan.filter_files(uniscan.collect_files()?, &file_filter);
+
+    let scan = uniscan.scan_all_files(&script_filter, usize::MAX, files, &|_| {})?;
     print_all(&scan.items);
 
     eprintln!("{} items in {:?}", scan.count, start.elapsed());
@@ -41,6 +79,100 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// `uniscan schema <game> <Script> [--json-schema]`
+fn schema(args: impl Iterator<Item = String>) -> Result<()> {
+    let (flags, args): (Vec<_>, Vec<_>) = args.partition(|arg| arg.starts_with("--"));
+    let mut json_schema = false;
+    for flag in flags {
+        if flag == "--json-schema" {
+            json_schema = true;
+        } else {
+            anyhow::bail!("unknown flag '{flag}'");
+        }
+    }
+    let mut args = args.into_iter();
+    let game_dir = args.next().context("missing path to game")?;
+    let script_filter = args.next().context("missing name of Script")?;
+
+    let uniscan = UniScan::new(Path::new(&game_dir), ".")?;
+    let scripts = uniscan.find_scripts(&ScriptFilter::try_new(&script_filter)?)?;
+    if scripts.is_empty() {
+        anyhow::bail!("no script matching '{script_filter}'");
+    }
+
+    let schemas = scripts
+        .iter()
+        .map(|script| {
+            let schema = uniscan.schema(script)?;
+            Ok(match json_schema {
+                true => uniscan::schema::json_schema(&schema),
+                false => schema,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    print_all(&schemas);
+
+    Ok(())
+}
+
+/// `uniscan info <game>`
+fn info(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let game_dir = args.next().context("missing path to game")?;
+
+    let info = uniscan::info::GameInfo::load(Path::new(&game_dir))?;
+    println!("{}", uniscan::to_pretty_json(&info.to_val()));
+
+    Ok(())
+}
+
+/// `uniscan fsm <game> [name] [--dot]`
+fn fsm(args: impl Iterator<Item = String>) -> Result<()> {
+    let (flags, args): (Vec<_>, Vec<_>) = args.partition(|arg| arg.starts_with("--"));
+    let mut dot = false;
+    for flag in flags {
+        if flag == "--dot" {
+            dot = true;
+        } else {
+            anyhow::bail!("unknown flag '{flag}'");
+        }
+    }
+    let mut args = args.into_iter();
+    let game_dir = args.next().context("missing path to game")?;
+    let name = args.next().map(|name| name.to_lowercase());
+
+    let uniscan = UniScan::new(
+        Path::new(&game_dir),
+        "{ game_object: (try (go | path) catch null) } + fsm_graph",
+    )?;
+    let files = uniscan.collect_files()?;
+    let scan = uniscan.scan_all_files(
+        &ScriptFilter::try_new("=PlayMakerFSM")?,
+        usize::MAX,
+        files,
+        &|_| {},
+    )?;
+    let graphs: Vec<_> = scan
+        .items
+        .into_iter()
+        .filter(|graph| {
+            name.as_ref().is_none_or(|name| {
+                let label = uniscan::playmaker::graph_label(graph);
+                label.to_lowercase().contains(name)
+            })
+        })
+        .collect();
+
+    match dot {
+        true => graphs
+            .iter()
+            .for_each(|graph| print!("{}", uniscan::playmaker::to_dot(graph))),
+        false => print_all(&graphs),
+    }
+    eprintln!("{} FSMs", graphs.len());
+
+    Ok(())
+}
+
 fn print_all(all: &[jaq_json::Val]) {
     all.par_iter()
         .map(uniscan::to_pretty_json)
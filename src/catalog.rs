@@ -0,0 +1,368 @@
+//! Decoding of the addressables content catalog (`StreamingAssets/aa/catalog.json`).
+//!
+//! The catalog stores its resource locations in three base64 blobs: the keys, the buckets
+//! mapping each key to the locations it resolves to, and the location entries themselves.
+//! Only string keys are decoded, numeric keys are dependency hashes and of no interest here.
+
+use std::collections::HashSet;
+
+use anyhow::{Context as _, Result, bail, ensure};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use jaq_json::Val;
+use jaq_std::ValT as _;
+
+use crate::hierarchy::field;
+
+/// Suffix of the provider that loads asset bundles.
+const BUNDLE_PROVIDER: &str = "AssetBundleProvider";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub internal_id: String,
+    pub provider: String,
+    pub primary_key: String,
+    /// Every string key resolving to this location, including the primary key.
+    pub keys: Vec<String>,
+    /// Indices of the locations this one depends on, e.g. the bundles of an asset.
+    pub dependencies: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    pub locations: Vec<Location>,
+}
+
+/// An asset loaded from a bundle, as seen by the catalog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressableAsset {
+    pub address: String,
+    /// The asset's path in the project, e.g. `Assets/Prefabs/Enemy.prefab`.
+    pub asset_path: String,
+    pub labels: Vec<String>,
+    /// File name of the bundle containing the asset.
+    pub bundle: String,
+    /// File names of the other bundles the asset depends on.
+    pub bundle_deps: Vec<String>,
+}
+
+impl Catalog {
+    pub fn from_json(json: &[u8]) -> Result<Catalog> {
+        let catalog = jaq_json::read::parse_single(json)
+            .map_err(|e| anyhow::anyhow!("{e}"))
+            .context("catalog is not valid JSON")?;
+
+        let prefixes = strings(&catalog, "m_InternalIdPrefixes").unwrap_or_default();
+        let internal_ids = strings(&catalog, "m_InternalIds").context("missing m_InternalIds")?;
+        let providers = strings(&catalog, "m_ProviderIds").context("missing m_ProviderIds")?;
+        let key_data = blob(&catalog, "m_KeyDataString")?;
+        let bucket_data = blob(&catalog, "m_BucketDataString")?;
+        let entry_data = blob(&catalog, "m_EntryDataString")?;
+
+        let mut buckets = Reader::new(&bucket_data);
+        let buckets = (0..buckets.count()?)
+            .map(|_| {
+                let key_offset = buckets.i32()?;
+                let entries = (0..buckets.count()?)
+                    .map(|_| buckets.count())
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((string_key(&key_data, key_offset)?, entries))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut entries = Reader::new(&entry_data);
+        let mut locations = (0..entries.count()?)
+            .map(|_| {
+                let [
+                    internal_id,
+                    provider,
+                    dependency_key,
+                    _dep_hash,
+                    _data,
+                    primary_key,
+                    _type,
+                ] = [(); 7].map(|_| entries.i32());
+                let internal_id = lookup(&internal_ids, internal_id?)?;
+                let (primary_key, _) = lookup(&buckets, primary_key?)?;
+                let dependencies = match dependency_key? {
+                    -1 => Vec::new(),
+                    key => lookup(&buckets, key)?.1.clone(),
+                };
+                Ok(Location {
+                    internal_id: expand_prefix(internal_id, &prefixes),
+                    provider: lookup(&providers, provider?)?.clone(),
+                    primary_key: primary_key.clone().unwrap_or_default(),
+                    keys: Vec::new(),
+                    dependencies,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (key, entries) in &buckets {
+            let Some(key) = key else { continue };
+            for &entry in entries {
+                let location = locations.get_mut(entry).context("invalid bucket entry")?;
+                if !location.keys.contains(key) {
+                    location.keys.push(key.clone());
+                }
+            }
+        }
+
+        Ok(Catalog { locations })
+    }
+
+    /// The assets stored in bundles, deduplicated by bundle and asset path.
+    pub fn assets(&self) -> Vec<AddressableAsset> {
+        let bundle_name = |index: usize| {
+            let location = self.locations.get(index)?;
+            location
+                .provider
+                .ends_with(BUNDLE_PROVIDER)
+                .then(|| file_name(&location.internal_id).to_owned())
+        };
+
+        let mut seen = HashSet::new();
+        let mut assets = Vec::new();
+        for location in &self.locations {
+            if location.provider.ends_with(BUNDLE_PROVIDER) {
+                continue;
+            }
+            let mut bundles = location.dependencies.iter().filter_map(|&i| bundle_name(i));
+            let Some(bundle) = bundles.next() else {
+                continue;
+            };
+            let key = (bundle.clone(), location.internal_id.clone());
+            if !seen.insert(key) {
+                continue;
+            }
+
+            let labels = location
+                .keys
+                .iter()
+                .filter(|key| {
+                    **key != location.primary_key && **key != location.internal_id && !is_guid(key)
+                })
+                .cloned()
+                .collect();
+            assets.push(AddressableAsset {
+                address: location.primary_key.clone(),
+                asset_path: location.internal_id.clone(),
+                labels,
+                bundle,
+                bundle_deps: bundles.collect(),
+            });
+        }
+        assets
+    }
+}
+
+/// Bundles are referenced by a path below the build's runtime path, only the name identifies them.
+pub fn file_name(internal_id: &str) -> &str {
+    internal_id
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(internal_id)
+}
+
+/// The name of a scene as loaded by the `SceneProvider`, from its asset path, e.g. `Forest` for
+/// `Assets/Scenes/Forest.unity`.
+pub fn scene_name(asset_path: &str) -> Option<&str> {
+    file_name(asset_path).strip_suffix(".unity")
+}
+
+/// Asset GUIDs are registered as keys alongside the addresses and labels.
+fn is_guid(key: &str) -> bool {
+    key.len() == 32 && key.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Internal ids may start with `<n>#`, standing for the n-th of `m_InternalIdPrefixes`.
+fn expand_prefix(internal_id: &str, prefixes: &[String]) -> String {
+    if let Some((index, rest)) = internal_id.split_once('#')
+        && let Ok(index) = index.parse::<usize>()
+        && let Some(prefix) = prefixes.get(index)
+    {
+        return format!("{prefix}{rest}");
+    }
+    internal_id.to_owned()
+}
+
+fn lookup<T>(items: &[T], index: i32) -> Result<&T> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| items.get(index))
+        .with_context(|| format!("catalog index {index} out of range"))
+}
+
+/// Decode the key at `offset`, `None` for keys that aren't strings.
+fn string_key(data: &[u8], offset: i32) -> Result<Option<String>> {
+    let mut reader = Reader::new(data);
+    reader.pos = usize::try_from(offset).context("negative key offset")?;
+    let ty = reader.bytes(1)?[0];
+    Ok(match ty {
+        // ASCII
+        0 => {
+            let len = reader.count()?;
+            Some(String::from_utf8_lossy(reader.bytes(len)?).into_owned())
+        }
+        // UTF-16
+        1 => {
+            let len = reader.count()?;
+            let units: Vec<u16> = reader
+                .bytes(len)?
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        2..=7 => None,
+        other => bail!("unknown catalog key type {other}"),
+    })
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .context("catalog data is truncated")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+    fn count(&mut self) -> Result<usize> {
+        let count = self.i32()?;
+        ensure!(count >= 0, "negative count in catalog data");
+        Ok(count as usize)
+    }
+}
+
+fn strings(catalog: &Val, name: &str) -> Option<Vec<String>> {
+    let Val::Arr(items) = field(catalog, name)? else {
+        return None;
+    };
+    items
+        .iter()
+        .map(|item| Some(String::from_utf8_lossy(item.as_utf8_bytes()?).into_owned()))
+        .collect()
+}
+
+fn blob(catalog: &Val, name: &str) -> Result<Vec<u8>> {
+    let text = field(catalog, name)
+        .and_then(|value| value.as_utf8_bytes())
+        .with_context(|| format!("missing {name}"))?;
+    BASE64
+        .decode(text)
+        .with_context(|| format!("{name} is not valid base64"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddressableAsset, Catalog, scene_name};
+    use base64::Engine as _;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+
+    /// Serialize keys, buckets and entries the way the addressables build does.
+    fn catalog(keys: &[&str], buckets: &[&[i32]], entries: &[[i32; 7]]) -> String {
+        let mut key_data = (keys.len() as i32).to_le_bytes().to_vec();
+        let mut offsets = Vec::new();
+        for key in keys {
+            offsets.push(key_data.len() as i32);
+            key_data.push(0);
+            key_data.extend((key.len() as i32).to_le_bytes());
+            key_data.extend(key.as_bytes());
+        }
+
+        let mut bucket_data = (buckets.len() as i32).to_le_bytes().to_vec();
+        for (offset, entries) in offsets.iter().zip(buckets) {
+            bucket_data.extend(offset.to_le_bytes());
+            bucket_data.extend((entries.len() as i32).to_le_bytes());
+            entries
+                .iter()
+                .for_each(|entry| bucket_data.extend(entry.to_le_bytes()));
+        }
+
+        let mut entry_data = (entries.len() as i32).to_le_bytes().to_vec();
+        for entry in entries {
+            entry
+                .iter()
+                .for_each(|v| entry_data.extend(v.to_le_bytes()));
+        }
+
+        format!(
+            r#"{{
+                "m_InternalIdPrefixes": ["{{RuntimePath}}/StandaloneWindows64"],
+                "m_InternalIds": ["0#/enemies_assets_all.bundle", "Assets/Enemies/Crawler.prefab"],
+                "m_ProviderIds": [
+                    "UnityEngine.ResourceManagement.ResourceProviders.AssetBundleProvider",
+                    "UnityEngine.ResourceManagement.ResourceProviders.BundledAssetProvider"
+                ],
+                "m_KeyDataString": "{}",
+                "m_BucketDataString": "{}",
+                "m_EntryDataString": "{}"
+            }}"#,
+            BASE64.encode(key_data),
+            BASE64.encode(bucket_data),
+            BASE64.encode(entry_data),
+        )
+    }
+
+    #[test]
+    fn decodes_assets_with_labels_and_bundles() {
+        let json = catalog(
+            &[
+                "enemies_assets_all.bundle",
+                "Enemies/Crawler",
+                "0123456789abcdef0123456789abcdef",
+                "enemy",
+            ],
+            &[&[0], &[1], &[1], &[1]],
+            &[
+                // internal id, provider, dependency key, dep hash, data, primary key, type
+                [0, 0, -1, 0, -1, 0, 0],
+                [1, 1, 0, 0, -1, 1, 0],
+            ],
+        );
+        let catalog = Catalog::from_json(json.as_bytes()).unwrap();
+
+        assert_eq!(
+            catalog.locations[0].internal_id,
+            "{RuntimePath}/StandaloneWindows64/enemies_assets_all.bundle"
+        );
+        assert_eq!(catalog.locations[1].dependencies, [0]);
+        assert_eq!(
+            catalog.assets(),
+            [AddressableAsset {
+                address: "Enemies/Crawler".into(),
+                asset_path: "Assets/Enemies/Crawler.prefab".into(),
+                labels: vec!["enemy".into()],
+                bundle: "enemies_assets_all.bundle".into(),
+                bundle_deps: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let json = catalog(&["a"], &[&[0]], &[[0, 0, -1, 0, -1, 0, 0]]).replace(
+            r#""m_EntryDataString": ""#,
+            r#""m_EntryDataString": "AQAAAA==", "unused": ""#,
+        );
+        assert!(Catalog::from_json(json.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn scene_names_from_asset_paths() {
+        assert_eq!(scene_name("Assets/Scenes/Forest.unity"), Some("Forest"));
+        assert_eq!(scene_name("Assets/Prefabs/Enemy.prefab"), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Loose files scanned next to the game, e.g. the bundles of mods or DLC.
+//!
+//! Extra files are loaded through the game's environment, so their external references resolve
+//! against the base game like those of its own files.
+
+use std::fs::File;
+use std::io::{BufReader, Read as _};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use rabex_env::addressables::ArchivePath;
+
+/// Signatures of AssetBundle archives, as opposed to plain serialized files.
+const BUNDLE_SIGNATURES: [&[u8]; 3] = [b"UnityFS\0", b"UnityWeb\0", b"UnityRaw\0"];
+
+/// Files below `path` to scan: `path` itself if it is a file, otherwise every file in it,
+/// recursively and in sorted order. Hidden files and `.manifest`s are skipped.
+pub fn expand(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        anyhow::ensure!(path.exists(), "{} does not exist", path.display());
+        return Ok(vec![path.to_owned()]);
+    }
+
+    let mut files = Vec::new();
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        let name = entry.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.') || name.ends_with(".manifest") {
+            continue;
+        }
+        match entry.is_dir() {
+            true => files.extend(expand(&entry)?),
+            false => files.push(entry),
+        }
+    }
+    Ok(files)
+}
+
+pub fn is_bundle(path: &Path) -> Result<bool> {
+    let mut header = [0; 9];
+    let read = File::open(path)?.read(&mut header)?;
+    Ok(BUNDLE_SIGNATURES
+        .iter()
+        .any(|signature| header[..read].starts_with(signature)))
+}
+
+/// The paths to pass to `load_serialized` for an extra file: the file itself, or an
+/// [`ArchivePath`] per serialized file inside a bundle.
+pub fn serialized_paths(path: &Path) -> Result<Vec<PathBuf>> {
+    if !is_bundle(path)? {
+        return Ok(vec![path.to_owned()]);
+    }
+
+    let bundle_path = path.to_string_lossy();
+    let reader = BufReader::new(File::open(path)?);
+    let bundle = rabex::files::BundleFileReader::from_reader(reader, &Default::default())
+        .with_context(|| format!("Could not read bundle {}", path.display()))?;
+    Ok(bundle
+        .files()
+        .iter()
+        .map(|entry| entry.path.as_str())
+        .filter(|cab| !cab.ends_with(".resource") && !cab.ends_with(".resS"))
+        .map(|cab| {
+            PathBuf::from(ArchivePath {
+                bundle: &bundle_path,
+                file: cab,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, is_bundle, serialized_paths};
+    use crate::asset_index::AssetIndex;
+    use crate::inheritance::GameTypes;
+    use crate::query::QueryRunner;
+    use jaq_json::Val;
+    use rabex::files::bundlefile::{BundleFileBuilder, CompressionType};
+    use rabex::objects::pptr::PathId;
+    use rabex::tpk::TpkTypeTreeBlob;
+    use rabex::typetree::typetree_cache::sync::TypeTreeCache;
+    use rabex_env::Environment;
+    use rabex_env::resolver::GameFiles;
+    use rabex_env_testkit::Flat;
+    use std::fs::File;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn expand_walks_directories_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        for file in [
+            "b.bundle",
+            "a.bundle",
+            "a.bundle.manifest",
+            ".hidden",
+            "sub/c.assets",
+        ] {
+            std::fs::write(dir.path().join(file), []).unwrap();
+        }
+
+        let files = expand(dir.path()).unwrap();
+        let expected = ["a.bundle", "b.bundle", "sub/c.assets"].map(|file| dir.path().join(file));
+        assert_eq!(files, expected);
+
+        let single = dir.path().join("a.bundle");
+        assert_eq!(expand(&single).unwrap(), [single]);
+        assert!(expand(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn bundles_are_recognized_by_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("mod.bundle");
+        std::fs::write(&bundle, b"UnityFS\0\0\0\0\x08").unwrap();
+        let assets = dir.path().join("mod.assets");
+        std::fs::write(&assets, [0; 32]).unwrap();
+        let empty = dir.path().join("empty");
+        std::fs::write(&empty, []).unwrap();
+
+        assert!(is_bundle(&bundle).unwrap());
+        assert!(!is_bundle(&assets).unwrap());
+        assert!(!is_bundle(&empty).unwrap());
+    }
+
+    /// A game with a single scene, whose directory must outlive the env.
+    fn game_env() -> (tempfile::TempDir, Environment) {
+        let (level0, _) = Flat::new(&["Player"]).write();
+        let game = tempfile::tempdir().unwrap();
+        let data_dir = game.path().join("Game_Data");
+        std::fs::create_dir(&data_dir).unwrap();
+        std::fs::write(data_dir.join("level0"), level0).unwrap();
+
+        let game_files = GameFiles::probe(game.path()).unwrap();
+        let env = Environment::new(game_files, TypeTreeCache::new(TpkTypeTreeBlob::embedded()));
+        (game, env)
+    }
+
+    /// The name of the GameObject `go_id` in the file loaded from `path`, reached through its
+    /// first component so that the PPtrs read from the file have to deref back into it.
+    fn game_object_name(env: &Environment, path: &str, go_id: PathId) -> Vec<Val> {
+        assert!(env.load_serialized(path).is_ok());
+        let runner = QueryRunner::new(
+            "deref | .m_Component[0].component | deref | .m_GameObject | deref | .m_Name",
+        )
+        .unwrap();
+        let mut pptr = jaq_json::Map::default();
+        pptr.insert("file".to_string().into(), path.to_owned().into());
+        pptr.insert("path_id".to_string().into(), (go_id as isize).into());
+        runner
+            .exec(
+                env,
+                &AssetIndex::default(),
+                &GameTypes::default(),
+                Val::obj(pptr),
+            )
+            .unwrap()
+    }
+
+    /// A bundle outside the game directory is loaded through the game's env by the absolute
+    /// `ArchivePath` from `serialized_paths`, and PPtrs qualified with that path resolve back
+    /// into it.
+    #[test]
+    fn loose_bundles_load_through_the_game_env() {
+        let (_game, env) = game_env();
+
+        let (cab, go_ids) = Flat::new(&["Modded"]).write();
+        let mods = tempfile::tempdir().unwrap();
+        let bundle = mods.path().join("mod.bundle");
+        let mut builder = BundleFileBuilder::unityfs(7, "2020.2.2f1");
+        builder.add_file("CAB-mod", cab);
+        builder
+            .write(&mut File::create(&bundle).unwrap(), CompressionType::None)
+            .unwrap();
+        assert!(is_bundle(&bundle).unwrap());
+
+        let paths = serialized_paths(&bundle).unwrap();
+        assert_eq!(paths.len(), 1);
+        let path = crate::format_path(&paths[0]);
+        assert_eq!(
+            game_object_name(&env, &path, go_ids[0]),
+            vec![val(r#""Modded""#)]
+        );
+    }
+
+    /// A loose serialized file is loaded by its own absolute path.
+    #[test]
+    fn loose_serialized_files_load_through_the_game_env() {
+        let (_game, env) = game_env();
+
+        let (bytes, go_ids) = Flat::new(&["Modded"]).write();
+        let mods = tempfile::tempdir().unwrap();
+        let assets = mods.path().join("mod.assets");
+        std::fs::write(&assets, bytes).unwrap();
+        assert!(!is_bundle(&assets).unwrap());
+
+        let paths = serialized_paths(&assets).unwrap();
+        assert_eq!(paths, [assets.clone()]);
+        let path = crate::format_path(&paths[0]);
+        assert_eq!(
+            game_object_name(&env, &path, go_ids[0]),
+            vec![val(r#""Modded""#)]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Filters on the files of a scan, checked on their paths so that skipped files are never loaded.
+
+/// Which files to scan. The default scans every file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileFilter {
+    /// Glob on the file name, or the bundle name for files inside a bundle, e.g. `*enemies*`.
+    /// Case insensitive, empty matches everything.
+    pub glob: String,
+    /// Only files belonging to one of these scenes, see [`crate::UniScan::file_scene`].
+    pub scenes: Vec<String>,
+    /// Only files inside addressable bundles.
+    pub only_addressables: bool,
+    /// Skip files inside bundles, i.e. scan only the game's own serialized files.
+    pub exclude_bundles: bool,
+}
+
+impl FileFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == FileFilter::default()
+    }
+
+    /// Parse a comma separated list of scene names, as entered in the UI.
+    pub fn set_scenes(&mut self, scenes: &str) {
+        self.scenes = scenes
+            .split(',')
+            .map(str::trim)
+            .filter(|scene| !scene.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+    }
+
+    pub fn matches_name(&self, name: &str) -> bool {
+        self.glob.is_empty() || glob_matches(&self.glob.to_lowercase(), &name.to_lowercase())
+    }
+}
+
+/// Match `text` against a glob with `*` for any run of characters and `?` for a single one.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileFilter, glob_matches};
+
+    #[test]
+    fn globs_match_wildcards() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("level?", "level3"));
+        assert!(!glob_matches("level?", "level12"));
+        assert!(glob_matches("*enemies*", "enemies_assets_all.bundle"));
+        assert!(glob_matches(
+            "sharedassets*.assets",
+            "sharedassets12.assets"
+        ));
+        assert!(!glob_matches(
+            "sharedassets*.assets",
+            "sharedassets12.assets.resS"
+        ));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("a*b*c", "aXbYbZ"));
+    }
+
+    #[test]
+    fn names_match_case_insensitively() {
+        let filter = FileFilter {
+            glob: "*Enemies*".into(),
+            ..Default::default()
+        };
+        assert!(filter.matches_name("ENEMIES_assets_all.bundle"));
+        assert!(!filter.matches_name("level0"));
+        assert!(FileFilter::default().matches_name("level0"));
+    }
+
+    #[test]
+    fn scenes_are_comma_separated() {
+        let mut filter = FileFilter::default();
+        filter.set_scenes(" Town, Forest ,,");
+        assert_eq!(filter.scenes, ["Town", "Forest"]);
+        filter.set_scenes("");
+        assert!(filter.is_empty());
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+
+use anyhow::{Context as _, Result};
+use jaq_json::Val;
+use jaq_std::ValT as _;
+use rabex::objects::pptr::{FileId, PathId};
+use rabex::objects::{ClassId, PPtr};
+use rabex::typetree::TypeTreeProvider;
+use rabex_env::handle::SerializedFileHandle;
+use rabex_env::resolver::EnvResolver;
+use rabex_env::unity::types::MonoBehaviour;
+
+/// A GameObject in a scene, with its components and child GameObjects in transform order.
+#[derive(Debug, Clone)]
+pub struct SceneNode {
+    pub name: String,
+    pub game_object: PathId,
+    pub components: Vec<SceneComponent>,
+    pub children: Vec<SceneNode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneComponent {
+    pub path_id: PathId,
+    /// The component's class, unknown if it couldn't be read.
+    pub class_id: Option<ClassId>,
+    /// The script's full name, for MonoBehaviours.
+    pub script: Option<String>,
+    /// Why the component couldn't be read.
+    pub error: Option<String>,
+}
+
+/// Build the GameObject tree of a scene file, starting from the transforms without a parent.
+///
+/// Roots are in `m_RootOrder` order where the transforms have one and in file order otherwise,
+/// children in the order of their parent's `m_Children`. References into other files are
+/// skipped, a scene's hierarchy is always local.
+pub fn scene_hierarchy<R: EnvResolver, P: TypeTreeProvider>(
+    file: &SerializedFileHandle<'_, R, P>,
+) -> Result<Vec<SceneNode>> {
+    let mut transforms = Vec::new();
+    for info in file.file.objects() {
+        if matches!(info.m_ClassID, ClassId::Transform | ClassId::RectTransform) {
+            transforms.push(info.m_PathID);
+        }
+    }
+    hierarchy(file, &transforms)
+}
+
+/// The objects of a scene file that its hierarchy is built from.
+trait SceneObjects {
+    fn read(&self, path_id: PathId) -> Result<Val>;
+    /// The class of a component, and the script's full name for MonoBehaviours.
+    fn component(&self, path_id: PathId) -> Result<(ClassId, Option<String>)>;
+}
+
+impl<R: EnvResolver, P: TypeTreeProvider> SceneObjects for SerializedFileHandle<'_, R, P> {
+    fn read(&self, path_id: PathId) -> Result<Val> {
+        let object = self.deref(PPtr::local(path_id).typed::<Val>())?;
+        object
+            .read()
+            .with_context(|| format!("Failed to read object {path_id}"))
+    }
+
+    fn component(&self, path_id: PathId) -> Result<(ClassId, Option<String>)> {
+        let class_id = self
+            .deref(PPtr::local(path_id).typed::<()>())?
+            .object
+            .info
+            .m_ClassID;
+        let script = match class_id {
+            ClassId::MonoBehaviour => self
+                .deref(PPtr::local(path_id).typed::<MonoBehaviour>())?
+                .mono_script()?
+                .map(|script| script.full_name().into_owned()),
+            _ => None,
+        };
+        Ok((class_id, script))
+    }
+}
+
+fn hierarchy(objects: &impl SceneObjects, transforms: &[PathId]) -> Result<Vec<SceneNode>> {
+    // Transforms already placed in the tree, so that malformed `m_Children` cycles terminate
+    let mut visited = HashSet::new();
+    let mut roots = Vec::new();
+    for &path_id in transforms {
+        let transform = objects.read(path_id)?;
+        if local_pptr(&transform, "m_Father").is_none() {
+            visited.insert(path_id);
+            let root_order = field(&transform, "m_RootOrder").and_then(|order| order.as_isize());
+            roots.push((root_order, node(objects, &transform, &mut visited)?));
+        }
+    }
+    // Stable, so roots without an order stay in file order
+    roots.sort_by_key(|(root_order, _)| root_order.unwrap_or(isize::MAX));
+    Ok(roots.into_iter().map(|(_, root)| root).collect())
+}
+
+fn node(
+    objects: &impl SceneObjects,
+    transform: &Val,
+    visited: &mut HashSet<PathId>,
+) -> Result<SceneNode> {
+    let game_object =
+        local_pptr(transform, "m_GameObject").context("transform without GameObject")?;
+    let go = objects.read(game_object)?;
+
+    let name = field(&go, "m_Name")
+        .and_then(|name| name.as_utf8_bytes())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .unwrap_or_default();
+
+    let mut components = Vec::new();
+    if let Some(Val::Arr(pairs)) = field(&go, "m_Component") {
+        for pair in pairs.iter() {
+            let Some(path_id) = field(pair, "component").and_then(local_path_id) else {
+                continue;
+            };
+            let component = match objects.component(path_id) {
+                Ok((class_id, script)) => SceneComponent {
+                    path_id,
+                    class_id: Some(class_id),
+                    script,
+                    error: None,
+                },
+                Err(e) => SceneComponent {
+                    path_id,
+                    class_id: None,
+                    script: None,
+                    error: Some(format!("{e:#}")),
+                },
+            };
+            components.push(component);
+        }
+    }
+
+    let mut children = Vec::new();
+    if let Some(Val::Arr(child_pptrs)) = field(transform, "m_Children") {
+        for child in child_pptrs.iter() {
+            let Some(child) = local_path_id(child) else {
+                continue;
+            };
+            if !visited.insert(child) {
+                continue;
+            }
+            children.push(node(objects, &objects.read(child)?, visited)?);
+        }
+    }
+
+    Ok(SceneNode {
+        name,
+        game_object,
+        components,
+        children,
+    })
+}
+
+pub(crate) fn field<'a>(v: &'a Val, name: &str) -> Option<&'a Val> {
+    match v {
+        Val::Obj(map) => map
+            .iter()
+            .find(|(k, _)| k.as_utf8_bytes() == Some(name.as_bytes()))
+            .map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+fn local_pptr(v: &Val, name: &str) -> Option<PathId> {
+    field(v, name).and_then(local_path_id)
+}
+
+/// The path id of a raw (unqualified) `{m_FileID, m_PathID}` PPtr into the same file.
+pub(crate) fn local_path_id(pptr: &Val) -> Option<PathId> {
+    let file_id = field(pptr, "m_FileID")?.as_isize()?;
+    let path_id = field(pptr, "m_PathID")?.as_isize()?;
+    let pptr = PPtr::new(FileId::new(file_id as i32), path_id as PathId).optional()?;
+    pptr.is_local().then_some(pptr.m_PathID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SceneObjects, hierarchy, scene_hierarchy};
+    use anyhow::{Context as _, Result};
+    use jaq_json::{Rc, Val};
+    use rabex::objects::ClassId;
+    use rabex::objects::pptr::PathId;
+    use rabex_env_testkit::{Flat, with_handle};
+    use std::collections::HashMap;
+
+    /// Objects by path id, with the class and script of each component.
+    #[derive(Default)]
+    struct Objects {
+        values: HashMap<PathId, Val>,
+        components: HashMap<PathId, (ClassId, Option<String>)>,
+    }
+
+    impl SceneObjects for Objects {
+        fn read(&self, path_id: PathId) -> Result<Val> {
+            self.values.get(&path_id).cloned().context("missing object")
+        }
+        fn component(&self, path_id: PathId) -> Result<(ClassId, Option<String>)> {
+            self.components
+                .get(&path_id)
+                .cloned()
+                .context("missing component")
+        }
+    }
+
+    fn pptr(path_id: PathId) -> String {
+        format!(r#"{{"m_FileID": 0, "m_PathID": {path_id}}}"#)
+    }
+
+    impl Objects {
+        /// Add a GameObject with a transform `transform` and further `components`.
+        fn add(
+            &mut self,
+            name: &str,
+            transform: PathId,
+            father: PathId,
+            children: &[PathId],
+            components: &[(PathId, ClassId, Option<&str>)],
+        ) {
+            let game_object = transform + 1000;
+            let children: Vec<_> = children.iter().map(|&child| pptr(child)).collect();
+            let transform_json = format!(
+                r#"{{"m_GameObject": {}, "m_Father": {}, "m_Children": [{}]}}"#,
+                pptr(game_object),
+                pptr(father),
+                children.join(", "),
+            );
+            self.values.insert(transform, val(&transform_json));
+
+            let mut component_pptrs = vec![format!(r#"{{"component": {}}}"#, pptr(transform))];
+            self.components
+                .insert(transform, (ClassId::Transform, None));
+            for &(path_id, class_id, script) in components {
+                component_pptrs.push(format!(r#"{{"component": {}}}"#, pptr(path_id)));
+                self.components
+                    .insert(path_id, (class_id, script.map(ToOwned::to_owned)));
+            }
+            let go_json = format!(
+                r#"{{"m_Name": "{name}", "m_Component": [{}]}}"#,
+                component_pptrs.join(", ")
+            );
+            self.values.insert(game_object, val(&go_json));
+        }
+
+        fn set_root_order(&mut self, transform: PathId, root_order: isize) {
+            let Some(Val::Obj(map)) = self.values.get_mut(&transform) else {
+                panic!("no transform {transform}");
+            };
+            Rc::make_mut(map).insert("m_RootOrder".to_string().into(), root_order.into());
+        }
+    }
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn children_are_in_m_children_order() {
+        let mut objects = Objects::default();
+        objects.add("Root", 10, 0, &[12, 11], &[]);
+        objects.add("B", 11, 10, &[], &[]);
+        objects.add("A", 12, 10, &[13], &[]);
+        objects.add("A1", 13, 12, &[], &[]);
+
+        let roots = hierarchy(&objects, &[10, 11, 12, 13]).unwrap();
+        assert_eq!(roots.len(), 1);
+        let names: Vec<_> = roots[0].children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["A", "B"]);
+        assert_eq!(roots[0].children[0].children[0].name, "A1");
+        assert_eq!(roots[0].game_object, 1010);
+    }
+
+    #[test]
+    fn components_list_their_class_and_script() {
+        let mut objects = Objects::default();
+        objects.add(
+            "Door",
+            10,
+            0,
+            &[],
+            &[
+                (20, ClassId::MonoBehaviour, Some("Game.Door")),
+                (21, ClassId::BoxCollider, None),
+            ],
+        );
+
+        let roots = hierarchy(&objects, &[10]).unwrap();
+        let components: Vec<_> = roots[0]
+            .components
+            .iter()
+            .map(|c| (c.path_id, c.class_id, c.script.as_deref()))
+            .collect();
+        assert_eq!(
+            components,
+            [
+                (10, Some(ClassId::Transform), None),
+                (20, Some(ClassId::MonoBehaviour), Some("Game.Door")),
+                (21, Some(ClassId::BoxCollider), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreadable_components_keep_their_error() {
+        let mut objects = Objects::default();
+        objects.add("Door", 10, 0, &[], &[(20, ClassId::MonoBehaviour, None)]);
+        objects.components.remove(&20);
+
+        let roots = hierarchy(&objects, &[10]).unwrap();
+        let door = &roots[0].components[1];
+        assert_eq!((door.path_id, door.class_id), (20, None));
+        assert_eq!(door.error.as_deref(), Some("missing component"));
+    }
+
+    #[test]
+    fn roots_are_in_root_order() {
+        let mut objects = Objects::default();
+        objects.add("Second", 10, 0, &[], &[]);
+        objects.add("First", 11, 0, &[], &[]);
+        objects.add("Unordered", 12, 0, &[], &[]);
+        objects.set_root_order(10, 1);
+        objects.set_root_order(11, 0);
+
+        let roots = hierarchy(&objects, &[10, 11, 12]).unwrap();
+        let names: Vec<_> = roots.iter().map(|root| root.name.as_str()).collect();
+        assert_eq!(names, ["First", "Second", "Unordered"]);
+    }
+
+    #[test]
+    fn cyclic_children_are_visited_once() {
+        let mut objects = Objects::default();
+        objects.add("Root", 10, 0, &[11], &[]);
+        // A malformed child listing its ancestor and itself as children
+        objects.add("Child", 11, 10, &[10, 11], &[]);
+
+        let roots = hierarchy(&objects, &[10, 11]).unwrap();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].children.len(), 1);
+        assert!(roots[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn flat_scene_has_one_root_per_game_object() {
+        let (bytes, go_ids) = Flat::new(&["Player", "Camera"]).write();
+
+        with_handle("level0", bytes, |file| {
+            let roots = scene_hierarchy(file).unwrap();
+
+            let names: Vec<_> = roots.iter().map(|root| root.name.as_str()).collect();
+            assert_eq!(names, ["Player", "Camera"]);
+            assert_eq!(roots[0].game_object, go_ids[0]);
+            assert!(roots.iter().all(|root| root.children.is_empty()));
+        });
+    }
+}
@@ -0,0 +1,163 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    /// `.`, `.field`, `.."`
+    Field,
+    /// `$name`
+    Variable,
+    /// A filter call like `deref` or `select`.
+    Ident,
+    String,
+    Number,
+    Operator,
+    Comment,
+    Whitespace,
+}
+
+const KEYWORDS: &[&str] = &[
+    "def", "if", "then", "elif", "else", "end", "as", "reduce", "foreach", "try", "catch", "label",
+    "import", "include", "and", "or", "not", "__loc__",
+];
+
+/// Split a jq query into highlightable tokens. This is a lexer for display only: it never fails,
+/// unterminated strings simply extend to the end of the input, and everything it doesn't
+/// recognize is an [`TokenKind::Operator`].
+pub fn tokenize(query: &str) -> Vec<(Range<usize>, TokenKind)> {
+    let bytes = query.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let ident_end = |mut i: usize| {
+        while i < bytes.len() && is_ident(bytes[i]) {
+            i += 1;
+        }
+        i
+    };
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let b = bytes[i];
+        let kind = if b.is_ascii_whitespace() {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            TokenKind::Whitespace
+        } else if b == b'#' {
+            i = query[i..].find('\n').map_or(bytes.len(), |end| i + end);
+            TokenKind::Comment
+        } else if b == b'"' {
+            i += 1;
+            // `\(..)` interpolations are part of the string as far as highlighting goes
+            while i < bytes.len() && bytes[i] != b'"' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(bytes.len());
+            TokenKind::String
+        } else if b.is_ascii_digit() {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                i += 1;
+            }
+            TokenKind::Number
+        } else if b == b'.' {
+            i += 1;
+            if bytes.get(i) == Some(&b'.') {
+                i += 1;
+            } else if bytes.get(i) == Some(&b'"') {
+                // `."quoted field"` is a field, the string gets its own token
+            } else {
+                i = ident_end(i);
+            }
+            TokenKind::Field
+        } else if b == b'$' {
+            i = ident_end(i + 1);
+            TokenKind::Variable
+        } else if is_ident(b) {
+            i = ident_end(i);
+            match KEYWORDS.contains(&&query[start..i]) {
+                true => TokenKind::Keyword,
+                false => TokenKind::Ident,
+            }
+        } else {
+            // multi-byte characters only ever show up in strings and comments in valid queries
+            i += query[i..].chars().next().map_or(1, char::len_utf8);
+            TokenKind::Operator
+        };
+        tokens.push((start..i, kind));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenKind::*;
+    use super::{TokenKind, tokenize};
+
+    fn kinds(query: &str) -> Vec<(&str, TokenKind)> {
+        tokenize(query)
+            .into_iter()
+            .filter(|(_, kind)| *kind != Whitespace)
+            .map(|(range, kind)| (&query[range], kind))
+            .collect()
+    }
+
+    #[test]
+    fn fields_idents_and_operators() {
+        assert_eq!(
+            kinds(".m_GameObject | deref | .m_Name"),
+            [
+                (".m_GameObject", Field),
+                ("|", Operator),
+                ("deref", Ident),
+                ("|", Operator),
+                (".m_Name", Field),
+            ],
+        );
+    }
+
+    #[test]
+    fn keywords_variables_numbers_and_strings() {
+        assert_eq!(
+            kinds(r#"if $x > 2.5 then "a \"b\"" else null end"#),
+            [
+                ("if", Keyword),
+                ("$x", Variable),
+                (">", Operator),
+                ("2.5", Number),
+                ("then", Keyword),
+                (r#""a \"b\"""#, String),
+                ("else", Keyword),
+                ("null", Ident),
+                ("end", Keyword),
+            ],
+        );
+    }
+
+    #[test]
+    fn comments_run_to_the_end_of_the_line() {
+        assert_eq!(kinds("# note\n."), [("# note", Comment), (".", Field)]);
+    }
+
+    #[test]
+    fn unterminated_string_covers_the_rest() {
+        assert_eq!(
+            kinds(r#"select(.a == "ab"#),
+            [
+                ("select", Ident),
+                ("(", Operator),
+                (".a", Field),
+                ("=", Operator),
+                ("=", Operator),
+                (r#""ab"#, String),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens_cover_the_whole_query() {
+        let query = "{ name: go|path, n: 1 } # ünïcode";
+        let covered: usize = tokenize(query).iter().map(|(range, _)| range.len()).sum();
+        assert_eq!(covered, query.len());
+    }
+}
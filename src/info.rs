@@ -0,0 +1,233 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use jaq_json::Val;
+use jaq_std::ValT as _;
+use rabex::objects::{ClassId, PPtr};
+use rabex::tpk::TpkTypeTreeBlob;
+use rabex::typetree::typetree_cache::sync::TypeTreeCache;
+use rabex_env::Environment;
+use rabex_env::resolver::GameFiles;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptingBackend {
+    Mono,
+    Il2Cpp,
+}
+
+impl fmt::Display for ScriptingBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScriptingBackend::Mono => "Mono",
+            ScriptingBackend::Il2Cpp => "IL2CPP",
+        })
+    }
+}
+
+/// An overview of a game, cheap enough to compute without scanning any objects.
+#[derive(Debug, Clone)]
+pub struct GameInfo {
+    pub name: String,
+    /// As stored in the build settings, e.g. `2020.2.2f1`.
+    pub unity_version: String,
+    pub backend: ScriptingBackend,
+    pub serialized_files: usize,
+    /// Zero if the game doesn't use addressables.
+    pub addressable_bundles: usize,
+    pub scenes: Vec<String>,
+    /// Total size of the data folder in bytes.
+    pub data_size: u64,
+}
+
+impl GameInfo {
+    /// Probe `game_dir` and collect its info, see [`GameInfo::from_env`].
+    pub fn load(game_dir: &Path) -> Result<GameInfo> {
+        let game_files = GameFiles::probe(game_dir)?;
+        let env = Environment::new(game_files, TypeTreeCache::new(TpkTypeTreeBlob::embedded()));
+        let scenes = env
+            .build_settings()?
+            .scene_names()
+            .map(ToOwned::to_owned)
+            .collect();
+        GameInfo::from_env(&env, scenes)
+    }
+
+    pub fn from_env(env: &Environment, scenes: Vec<String>) -> Result<GameInfo> {
+        let (root, data) = game_dirs(&env.game_files.game_dir);
+
+        let addressable_bundles = match env.addressables()? {
+            Some(aa) => aa.cab_to_bundle.values().collect::<HashSet<_>>().len(),
+            None => 0,
+        };
+
+        Ok(GameInfo {
+            name: env.app_info()?.name,
+            unity_version: unity_version(env)?,
+            backend: scripting_backend(&root, &data),
+            serialized_files: env.game_files.serialized_files()?.len(),
+            addressable_bundles,
+            scenes,
+            data_size: dir_size(&data)?,
+        })
+    }
+
+    pub fn to_val(&self) -> Val {
+        let mut map = jaq_json::Map::default();
+        let mut insert = |key: &str, value: Val| {
+            map.insert(key.to_owned().into(), value);
+        };
+        insert("name", self.name.clone().into());
+        insert("unity_version", self.unity_version.clone().into());
+        insert("backend", self.backend.to_string().into());
+        insert("serialized_files", (self.serialized_files as isize).into());
+        insert(
+            "addressable_bundles",
+            (self.addressable_bundles as isize).into(),
+        );
+        insert(
+            "scenes",
+            self.scenes.iter().cloned().map(Val::from).collect(),
+        );
+        insert("data_size", (self.data_size as isize).into());
+        Val::obj(map)
+    }
+}
+
+/// Format a size in bytes with a binary unit, e.g. `1.5 GiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+/// IL2CPP builds ship a native `GameAssembly` next to the executable and the metadata in the
+/// data folder, instead of `Managed/*.dll`.
+pub fn scripting_backend(root: &Path, data: &Path) -> ScriptingBackend {
+    let game_assembly = ["GameAssembly.dll", "GameAssembly.so", "GameAssembly.dylib"]
+        .iter()
+        .any(|name| root.join(name).exists());
+    match game_assembly || data.join("il2cpp_data").is_dir() {
+        true => ScriptingBackend::Il2Cpp,
+        false => ScriptingBackend::Mono,
+    }
+}
+
+/// The folder containing the executable, and the `<Game>_Data` folder, for a directory as found
+/// by [`GameFiles::probe`].
+pub fn game_dirs(game_dir: &Path) -> (PathBuf, PathBuf) {
+    if game_dir.join("globalgamemanagers").exists() || game_dir.join("data.unity3d").exists() {
+        let root = game_dir.parent().unwrap_or(game_dir);
+        return (root.to_owned(), game_dir.to_owned());
+    }
+    let data = std::fs::read_dir(game_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().ends_with("_Data"))
+        });
+    (
+        game_dir.to_owned(),
+        data.unwrap_or_else(|| game_dir.to_owned()),
+    )
+}
+
+/// The `m_Version` of the BuildSettings object in `globalgamemanagers`.
+fn unity_version(env: &Environment) -> Result<String> {
+    let file = env
+        .load_serialized("globalgamemanagers")
+        .context("Could not load 'globalgamemanagers'")?;
+    let info = file
+        .file
+        .objects()
+        .find(|info| info.m_ClassID == ClassId::BuildSettings)
+        .context("no BuildSettings in 'globalgamemanagers'")?;
+    let build_settings = file
+        .deref(PPtr::local(info.m_PathID).typed::<Val>())?
+        .read()?;
+
+    let Val::Obj(map) = build_settings else {
+        anyhow::bail!("BuildSettings is not an object");
+    };
+    let version = map
+        .iter()
+        .find(|(k, _)| k.as_utf8_bytes() == Some(b"m_Version"))
+        .and_then(|(_, v)| v.as_utf8_bytes())
+        .context("BuildSettings without m_Version")?;
+    Ok(String::from_utf8_lossy(version).into_owned())
+}
+
+/// The total size of the files in `dir`. Symlinks are skipped, so that linked folders aren't
+/// counted twice and link loops terminate.
+fn dir_size(dir: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let metadata = std::fs::symlink_metadata(&path)?;
+        if metadata.is_symlink() {
+            continue;
+        }
+        size += match metadata.is_dir() {
+            true => dir_size(&path)?,
+            false => metadata.len(),
+        };
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScriptingBackend, dir_size, format_size, game_dirs, scripting_backend};
+
+    #[test]
+    fn sizes_use_binary_units() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn backend_from_game_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("Game_Data");
+        std::fs::create_dir(&data).unwrap();
+        std::fs::write(data.join("globalgamemanagers"), []).unwrap();
+
+        assert_eq!(game_dirs(dir.path()), (dir.path().to_owned(), data.clone()));
+        assert_eq!(game_dirs(&data), (dir.path().to_owned(), data.clone()));
+        assert_eq!(scripting_backend(dir.path(), &data), ScriptingBackend::Mono);
+
+        std::fs::write(dir.path().join("GameAssembly.dll"), []).unwrap();
+        assert_eq!(
+            scripting_backend(dir.path(), &data),
+            ScriptingBackend::Il2Cpp
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_size_skips_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+        std::fs::write(sub.join("data"), [0; 100]).unwrap();
+        std::os::unix::fs::symlink(&sub, dir.path().join("linked")).unwrap();
+        std::os::unix::fs::symlink(dir.path(), sub.join("loop")).unwrap();
+
+        assert_eq!(dir_size(dir.path()).unwrap(), 100);
+    }
+}
@@ -0,0 +1,432 @@
+//! The types of the game's managed assemblies: their hierarchy, for matching scripts by the
+//! classes they derive from and the interfaces they implement, and their fields, for naming the
+//! values of serialized enums.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context as _, Result};
+use dotnetdll::prelude::{
+    BaseType, Constant, MemberType, ReadOptions, Resolution, TypeSource, UserType,
+};
+use jaq_json::{Rc, Val};
+use jaq_std::ValT as _;
+use tracing::warn;
+
+/// Generic collections serialized like arrays.
+const LIST_TYPE: &str = "System.Collections.Generic.List`1";
+
+#[derive(Debug, Default)]
+pub struct TypeHierarchy {
+    /// Full name to the full names of the direct base class and the implemented interfaces.
+    bases: HashMap<String, Vec<String>>,
+    /// Full name to the instance fields declared by the type itself.
+    fields: HashMap<String, Vec<Field>>,
+    /// Full name of an enum to its named values.
+    enums: HashMap<String, Vec<(i64, String)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    /// Full name of the field's type, or of the element type for arrays and lists.
+    pub ty: String,
+    pub array: bool,
+}
+
+/// The [`TypeHierarchy`] of a game, read from its managed assemblies on first use.
+#[derive(Debug, Default)]
+pub struct GameTypes {
+    managed_dir: Option<PathBuf>,
+    types: OnceLock<TypeHierarchy>,
+}
+
+impl GameTypes {
+    /// The types of the assemblies in `managed_dir`, e.g. `<Game>_Data/Managed`.
+    pub fn new(managed_dir: PathBuf) -> Self {
+        GameTypes {
+            managed_dir: Some(managed_dir),
+            types: OnceLock::new(),
+        }
+    }
+
+    pub fn get(&self) -> &TypeHierarchy {
+        self.types.get_or_init(|| {
+            let Some(managed_dir) = &self.managed_dir else {
+                return TypeHierarchy::default();
+            };
+            TypeHierarchy::load(managed_dir).unwrap_or_else(|e| {
+                warn!("{e:?}");
+                TypeHierarchy::default()
+            })
+        })
+    }
+}
+
+/// How [`TypeHierarchy::name_enums`] presents enum values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumNames {
+    /// Leave the numbers.
+    #[default]
+    Off,
+    /// Add a `<field>_name` next to each enum field.
+    Sibling,
+    /// Replace the numbers with their names.
+    Replace,
+}
+
+impl TypeHierarchy {
+    /// Read the type definitions of every assembly in `managed_dir` except the framework's, see
+    /// [`is_framework_assembly`]. Assemblies that fail to parse are skipped with a warning.
+    pub fn load(managed_dir: &Path) -> Result<TypeHierarchy> {
+        let mut hierarchy = TypeHierarchy::default();
+        let entries = std::fs::read_dir(managed_dir)
+            .with_context(|| format!("Could not read {}", managed_dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "dll") {
+                continue;
+            }
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            if is_framework_assembly(&name) {
+                continue;
+            }
+            let read = std::fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| hierarchy.read_assembly(&bytes));
+            if let Err(e) = read {
+                warn!("Could not read types of {}: {e:?}", path.display());
+            }
+        }
+        Ok(hierarchy)
+    }
+
+    pub fn from_bases(bases: HashMap<String, Vec<String>>) -> TypeHierarchy {
+        TypeHierarchy {
+            bases,
+            ..Default::default()
+        }
+    }
+
+    fn read_assembly(&mut self, bytes: &[u8]) -> Result<()> {
+        let resolution =
+            Resolution::parse(bytes, ReadOptions::default()).map_err(|e| anyhow::anyhow!("{e}"))?;
+        for (_, definition) in resolution.enumerate_type_definitions() {
+            let extends = definition.extends.iter();
+            let implements = definition.implements.iter().map(|(_, interface)| interface);
+            let bases: Vec<String> = extends
+                .chain(implements)
+                .map(|base| type_source_name(&resolution, base))
+                .collect();
+            let name = definition.type_name();
+
+            if bases.first().is_some_and(|base| base == "System.Enum") {
+                let values = definition
+                    .fields
+                    .iter()
+                    .filter(|field| field.static_member)
+                    .filter_map(|field| {
+                        Some((
+                            constant_value(field.default.as_ref()?)?,
+                            field.name.to_string(),
+                        ))
+                    })
+                    .collect();
+                self.enums.insert(name.clone(), values);
+            } else {
+                let fields = definition
+                    .fields
+                    .iter()
+                    .filter(|field| !field.static_member)
+                    .filter_map(|field| field_type(&resolution, &field.name, &field.return_type))
+                    .collect();
+                self.fields.insert(name.clone(), fields);
+            }
+            self.bases.insert(name, bases);
+        }
+        Ok(())
+    }
+
+    /// Every type `full_name` derives from or implements, nearest first, excluding itself.
+    /// Types of assemblies outside the game, e.g. `System.Object`, are listed but not followed.
+    pub fn base_types(&self, full_name: &str) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut base_types = Vec::new();
+        let mut queue = VecDeque::from([full_name]);
+        while let Some(name) = queue.pop_front() {
+            for base in self.bases.get(name).into_iter().flatten() {
+                if seen.insert(base.as_str()) {
+                    base_types.push(base.clone());
+                    queue.push_back(base.as_str());
+                }
+            }
+        }
+        base_types
+    }
+
+    /// The name of an enum's value. `ty` is the enum's full name, or its name if that is unique.
+    pub fn enum_name(&self, ty: &str, value: i64) -> Option<&str> {
+        let values = self.enums.get(ty).or_else(|| {
+            let mut matching = self.enums.iter().filter(|(name, _)| {
+                name.rsplit_once(['.', '/'])
+                    .is_some_and(|(_, short_name)| short_name == ty)
+            });
+            match (matching.next(), matching.next()) {
+                (Some((_, values)), None) => Some(values),
+                _ => None,
+            }
+        })?;
+        values
+            .iter()
+            .find(|(v, _)| *v == value)
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Name the enum fields of a serialized object of type `ty`, including those of nested
+    /// structs, lists and inherited fields.
+    pub fn name_enums(&self, ty: &str, value: &mut Val, mode: EnumNames) {
+        if mode == EnumNames::Off {
+            return;
+        }
+        let Val::Obj(map) = value else {
+            return;
+        };
+        let fields: Vec<&Field> = std::iter::once(ty.to_owned())
+            .chain(self.base_types(ty))
+            .filter_map(|ty| self.fields.get(&ty))
+            .flatten()
+            .collect();
+        if fields.is_empty() {
+            return;
+        }
+
+        let map = Rc::make_mut(map);
+        let mut siblings = Vec::new();
+        for (key, field_value) in map.iter_mut() {
+            let Some(field) = fields
+                .iter()
+                .find(|field| key.as_utf8_bytes() == Some(field.name.as_bytes()))
+            else {
+                continue;
+            };
+            let name = |value: &Val| {
+                let name = self.enum_name(&field.ty, value.as_isize()? as i64)?;
+                Some(Val::from(name.to_owned()))
+            };
+
+            if !self.enums.contains_key(&field.ty) {
+                match (field.array, &mut *field_value) {
+                    (true, Val::Arr(elements)) => Rc::make_mut(elements)
+                        .iter_mut()
+                        .for_each(|element| self.name_enums(&field.ty, element, mode)),
+                    (false, value) => self.name_enums(&field.ty, value, mode),
+                    _ => {}
+                }
+                continue;
+            }
+            let named = match (field.array, &*field_value) {
+                (true, Val::Arr(elements)) => elements
+                    .iter()
+                    .map(|element| name(element).unwrap_or_else(|| element.clone()))
+                    .collect(),
+                (false, value) => match name(value) {
+                    Some(name) => name,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            match mode {
+                EnumNames::Replace => *field_value = named,
+                _ => siblings.push((format!("{}_name", field.name), named)),
+            }
+        }
+        for (key, named) in siblings {
+            map.insert(key.into(), named);
+        }
+    }
+}
+
+/// The field of a type, if it has a named type that may be an enum or contain some.
+fn field_type(resolution: &Resolution<'_>, name: &str, ty: &MemberType) -> Option<Field> {
+    let named = |ty: &MemberType| match ty {
+        MemberType::Base(base) => match &**base {
+            BaseType::Type { source, .. } => Some(type_source_name(resolution, source)),
+            _ => None,
+        },
+        _ => None,
+    };
+    let MemberType::Base(base) = ty else {
+        return None;
+    };
+    let (ty, array) = match &**base {
+        BaseType::Vector(_, element) => (named(element)?, true),
+        BaseType::Type {
+            source: TypeSource::Generic { base, parameters },
+            ..
+        } if user_type_name(resolution, base) == LIST_TYPE => (named(parameters.first()?)?, true),
+        _ => (named(ty)?, false),
+    };
+    Some(Field {
+        name: name.to_owned(),
+        ty,
+        array,
+    })
+}
+
+fn constant_value(constant: &Constant) -> Option<i64> {
+    Some(match *constant {
+        Constant::Int8(v) => v.into(),
+        Constant::UInt8(v) => v.into(),
+        Constant::Int16(v) => v.into(),
+        Constant::UInt16(v) => v.into(),
+        Constant::Int32(v) => v.into(),
+        Constant::UInt32(v) => v.into(),
+        Constant::Int64(v) => v,
+        Constant::UInt64(v) => v as i64,
+        _ => return None,
+    })
+}
+
+fn type_source_name(resolution: &Resolution<'_>, source: &TypeSource<MemberType>) -> String {
+    match source {
+        TypeSource::User(user_type) => user_type_name(resolution, user_type),
+        TypeSource::Generic { base, .. } => user_type_name(resolution, base),
+    }
+}
+
+fn user_type_name(resolution: &Resolution<'_>, user_type: &UserType) -> String {
+    match *user_type {
+        UserType::Definition(index) => resolution[index].type_name(),
+        UserType::Reference(index) => resolution[index].type_name(),
+    }
+}
+
+/// Assemblies of .NET and of the Unity engine, whose types are listed as bases but never
+/// followed, so reading them would only slow down loading. Unity packages like
+/// `Unity.TextMeshPro` are read, scripts derive from their classes.
+fn is_framework_assembly(name: &str) -> bool {
+    matches!(name, "mscorlib" | "netstandard" | "System" | "UnityEngine")
+        || ["System.", "UnityEngine.", "Mono."]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnumNames, Field, TypeHierarchy, is_framework_assembly};
+    use jaq_json::Val;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    fn field(name: &str, ty: &str, array: bool) -> Field {
+        Field {
+            name: name.into(),
+            ty: ty.into(),
+            array,
+        }
+    }
+
+    fn enemies() -> TypeHierarchy {
+        let mut hierarchy =
+            TypeHierarchy::from_bases([("Game.Crawler".into(), vec!["Game.Enemy".into()])].into());
+        hierarchy.fields.insert(
+            "Game.Enemy".into(),
+            vec![
+                field("kind", "Game.EnemyKind", false),
+                field("weaknesses", "Game.Element", true),
+                field("drop", "Game.Drop", false),
+            ],
+        );
+        hierarchy.fields.insert(
+            "Game.Drop".into(),
+            vec![field("element", "Game.Element", false)],
+        );
+        hierarchy.enums.insert(
+            "Game.EnemyKind".into(),
+            vec![(0, "Melee".into()), (1, "Ranged".into())],
+        );
+        hierarchy.enums.insert(
+            "Game.Element".into(),
+            vec![(0, "Fire".into()), (1, "Ice".into())],
+        );
+        hierarchy
+    }
+
+    #[test]
+    fn base_types_are_transitive_and_nearest_first() {
+        let hierarchy = TypeHierarchy::from_bases(
+            [
+                ("Game.Crawler", vec!["Game.Enemy", "Game.IDamageable"]),
+                ("Game.Enemy", vec!["Game.Character"]),
+                ("Game.Character", vec!["UnityEngine.MonoBehaviour"]),
+                ("Game.IDamageable", vec![]),
+            ]
+            .into_iter()
+            .map(|(name, bases)| (name.into(), bases.into_iter().map(Into::into).collect()))
+            .collect(),
+        );
+
+        assert_eq!(
+            hierarchy.base_types("Game.Crawler"),
+            [
+                "Game.Enemy",
+                "Game.IDamageable",
+                "Game.Character",
+                "UnityEngine.MonoBehaviour"
+            ]
+        );
+        assert!(hierarchy.base_types("Game.IDamageable").is_empty());
+        assert!(hierarchy.base_types("Unknown").is_empty());
+    }
+
+    #[test]
+    fn enum_names_by_full_or_unique_short_name() {
+        let hierarchy = enemies();
+        assert_eq!(hierarchy.enum_name("Game.EnemyKind", 1), Some("Ranged"));
+        assert_eq!(hierarchy.enum_name("EnemyKind", 0), Some("Melee"));
+        assert_eq!(hierarchy.enum_name("EnemyKind", 7), None);
+        assert_eq!(hierarchy.enum_name("Unknown", 0), None);
+    }
+
+    #[test]
+    fn names_nested_inherited_and_array_enums() {
+        let hierarchy = enemies();
+        let object = r#"{"kind": 1, "weaknesses": [0, 5], "drop": {"element": 1}, "hp": 3}"#;
+
+        let mut sibling = val(object);
+        hierarchy.name_enums("Game.Crawler", &mut sibling, EnumNames::Sibling);
+        assert_eq!(
+            sibling,
+            val(r#"{
+                "kind": 1, "weaknesses": [0, 5], "drop": {"element": 1, "element_name": "Ice"},
+                "hp": 3, "kind_name": "Ranged", "weaknesses_name": ["Fire", 5]
+            }"#)
+        );
+
+        let mut replaced = val(object);
+        hierarchy.name_enums("Game.Crawler", &mut replaced, EnumNames::Replace);
+        assert_eq!(
+            replaced,
+            val(
+                r#"{"kind": "Ranged", "weaknesses": ["Fire", 5], "drop": {"element": "Ice"}, "hp": 3}"#
+            )
+        );
+
+        let mut off = val(object);
+        hierarchy.name_enums("Game.Crawler", &mut off, EnumNames::Off);
+        assert_eq!(off, val(object));
+    }
+
+    #[test]
+    fn framework_assemblies_are_skipped() {
+        for name in ["mscorlib", "System.Core", "UnityEngine.CoreModule"] {
+            assert!(is_framework_assembly(name), "{name}");
+        }
+        for name in ["Assembly-CSharp", "Unity.TextMeshPro", "SystemsLib"] {
+            assert!(!is_framework_assembly(name), "{name}");
+        }
+    }
+}
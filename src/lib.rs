@@ -1,15 +1,32 @@
 use std::fmt::Write;
+pub mod asset_index;
+pub mod catalog;
+pub mod extra_files;
+pub mod file_filter;
+pub mod hierarchy;
+pub mod highlight;
+pub mod info;
+pub mod inheritance;
+pub mod managed_refs;
+pub mod playmaker;
 pub mod qualify_pptr;
 pub mod query;
+pub mod schema;
+pub mod search;
+pub mod table;
 
 // Re-exported so downstream crates (e.g. the UI) name the exact same `Val` type,
 // including the `sync` feature selection.
 pub use jaq_json;
+// Re-exported for `ValT`, the accessors on `Val`.
+pub use jaq_std;
 
+pub use file_filter::FileFilter;
 use query::QueryRunner;
 
 use anyhow::{Context, Result};
 use rabex::tpk::TpkTypeTreeBlob;
+use rabex::objects::pptr::PathId;
 use rabex::typetree::typetree_cache::sync::TypeTreeCache;
 use rabex_env::Environment;
 use rabex_env::addressables::ArchivePath;
@@ -35,31 +52,199 @@ pub struct UniScan {
     pub env: Arc<Environment>,
     pub scene_names: Vec<String>,
     pub query: QueryRunner,
+    pub index: asset_index::AssetIndex,
+    /// Loose serialized files and bundle entries scanned next to the game's own files,
+    /// see [`extra_files`].
+    pub extra_files: Vec<PathBuf>,
+    /// How enum fields of scanned objects are named.
+    pub enum_names: inheritance::EnumNames,
+    /// Whether scanned objects get the `_base_types` of their script.
+    pub with_base_types: bool,
+    /// The types of the game's managed assemblies, for `is:` filters, enum names and
+    /// `_base_types`.
+    pub types: inheritance::GameTypes,
 }
 
-#[derive(Clone, Default, PartialEq, Eq)]
+/// Selects scripts by name. A filter is a comma separated list of terms, matching scripts that
+/// match any term and none of the terms negated with `!`. Terms are
+/// - `text`: case insensitive substring of the full name, e.g. `enemy`
+/// - `=Name`: exactly the class name or the full name, e.g. `=Game.Enemy`
+/// - `/regex/`: regex on the full name
+///
+/// and can be prefixed with `ns:` or `asm:` to match the namespace or assembly name instead,
+/// e.g. `asm:=Assembly-CSharp, !ns:UnityEngine`, or with `is:` to match the script or any type it
+/// derives from or implements, e.g. `is:=Game.Enemy`. Terms can't contain commas.
+#[derive(Clone, Default)]
 pub struct ScriptFilter {
     filter: String,
+    include: Vec<ScriptPattern>,
+    exclude: Vec<ScriptPattern>,
+}
+
+#[derive(Clone)]
+struct ScriptPattern {
+    field: ScriptField,
+    matcher: Matcher,
+}
+
+#[derive(Clone, Copy)]
+enum ScriptField {
+    FullName,
+    Namespace,
+    Assembly,
+    /// The full name or any of the base types.
+    BaseType,
+}
+
+#[derive(Clone)]
+enum Matcher {
+    /// Lowercased.
+    Substring(String),
+    Exact(String),
+    /// `None` for an invalid regex, which matches nothing.
+    Regex(Option<regex::Regex>),
+}
+
+impl PartialEq for ScriptFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.filter == other.filter
+    }
 }
+impl Eq for ScriptFilter {}
+
 impl ScriptFilter {
     pub fn empty() -> Self {
         ScriptFilter::default()
     }
 
+    /// Parse a filter, where invalid regexes match nothing. See [`ScriptFilter::try_new`].
     pub fn new(filter: &str) -> ScriptFilter {
-        ScriptFilter {
-            filter: filter.trim().to_ascii_lowercase(),
-        }
+        ScriptFilter::parse(filter, false).expect("invalid regexes are allowed")
     }
 
-    pub fn matches(&self, script: &MonoScript) -> bool {
-        if self.filter.is_empty() {
-            return true;
+    /// Parse a filter, failing for invalid regexes.
+    pub fn try_new(filter: &str) -> Result<ScriptFilter> {
+        ScriptFilter::parse(filter, true)
+    }
+
+    fn parse(filter: &str, strict: bool) -> Result<ScriptFilter> {
+        let mut script_filter = ScriptFilter {
+            filter: filter.trim().to_owned(),
+            ..Default::default()
+        };
+        for term in filter.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (negated, term) = match term.strip_prefix('!') {
+                Some(term) => (true, term.trim_start()),
+                None => (false, term),
+            };
+            let (field, pattern) = if let Some(pattern) = term.strip_prefix("ns:") {
+                (ScriptField::Namespace, pattern)
+            } else if let Some(pattern) = term.strip_prefix("asm:") {
+                (ScriptField::Assembly, pattern)
+            } else if let Some(pattern) = term.strip_prefix("is:") {
+                (ScriptField::BaseType, pattern)
+            } else {
+                (ScriptField::FullName, term)
+            };
+
+            let matcher = if let Some(exact) = pattern.strip_prefix('=') {
+                Matcher::Exact(exact.to_owned())
+            } else if let Some(regex) = pattern
+                .strip_prefix('/')
+                .and_then(|pattern| pattern.strip_suffix('/'))
+            {
+                match regex::Regex::new(regex) {
+                    Ok(regex) => Matcher::Regex(Some(regex)),
+                    Err(e) if strict => return Err(e).context("Invalid script filter"),
+                    Err(_) => Matcher::Regex(None),
+                }
+            } else {
+                Matcher::Substring(pattern.to_lowercase())
+            };
+
+            let pattern = ScriptPattern { field, matcher };
+            match negated {
+                true => script_filter.exclude.push(pattern),
+                false => script_filter.include.push(pattern),
+            }
         }
-        let mut class = script.full_name();
-        class.to_mut().make_ascii_lowercase();
+        Ok(script_filter)
+    }
+
+    /// Whether the filter has `is:` terms, which need the script's base types to be checked.
+    pub fn needs_base_types(&self) -> bool {
+        self.include
+            .iter()
+            .chain(&self.exclude)
+            .any(|pattern| matches!(pattern.field, ScriptField::BaseType))
+    }
 
-        class.contains(&self.filter)
+    /// Check a script. `is:` terms look up the types it derives from in `types`, which is only
+    /// read for filters with such terms, see [`ScriptFilter::needs_base_types`].
+    pub fn matches(&self, script: &MonoScript, types: &inheritance::TypeHierarchy) -> bool {
+        self.matches_in(&script.assembly_name(), &script.full_name(), types)
+    }
+
+    /// Like [`ScriptFilter::matches`], for when only the script's names are known.
+    pub fn matches_in(
+        &self,
+        assembly: &str,
+        full_name: &str,
+        types: &inheritance::TypeHierarchy,
+    ) -> bool {
+        self.matches_parts(Some(assembly), full_name, types)
+    }
+
+    /// Like [`ScriptFilter::matches`], for when only the script's full name is known. Terms on
+    /// the assembly are ignored.
+    pub fn matches_name(&self, full_name: &str, types: &inheritance::TypeHierarchy) -> bool {
+        self.matches_parts(None, full_name, types)
+    }
+
+    fn matches_parts(
+        &self,
+        assembly: Option<&str>,
+        full_name: &str,
+        types: &inheritance::TypeHierarchy,
+    ) -> bool {
+        let assembly = assembly.map(|assembly| assembly.strip_suffix(".dll").unwrap_or(assembly));
+        let (namespace, _) = full_name.rsplit_once('.').unwrap_or(("", full_name));
+        let matches_text = |matcher: &Matcher, text: &str, is_full_name: bool| match matcher {
+            Matcher::Substring(substring) => text.to_lowercase().contains(substring),
+            Matcher::Exact(exact) => {
+                let class = text.rsplit_once('.').map_or(text, |(_, class)| class);
+                exact == text || (is_full_name && exact == class)
+            }
+            Matcher::Regex(regex) => regex.as_ref().is_some_and(|regex| regex.is_match(text)),
+        };
+        let base_types = std::cell::OnceCell::new();
+        // `None` if the pattern can't be checked
+        let pattern_matches = |pattern: &ScriptPattern| {
+            let text = match pattern.field {
+                ScriptField::FullName => full_name,
+                ScriptField::Namespace => namespace,
+                ScriptField::Assembly => assembly?,
+                ScriptField::BaseType => {
+                    let base_types = base_types.get_or_init(|| types.base_types(full_name));
+                    let mut types =
+                        std::iter::once(full_name).chain(base_types.iter().map(String::as_str));
+                    return Some(types.any(|ty| matches_text(&pattern.matcher, ty, true)));
+                }
+            };
+            let is_full_name = matches!(pattern.field, ScriptField::FullName);
+            Some(matches_text(&pattern.matcher, text, is_full_name))
+        };
+
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| pattern_matches(pattern).unwrap_or(true));
+        let excluded = self
+            .exclude
+            .iter()
+            .any(|pattern| pattern_matches(pattern).unwrap_or(false));
+        included && !excluded
     }
 }
 
@@ -99,6 +284,9 @@ impl UniScan {
         let env = Environment::new(game_files, tpk);
 
         let env = Arc::new(env);
+        let index = asset_index::AssetIndex::for_game(&env.game_files.game_dir);
+        let (_, data) = info::game_dirs(&env.game_files.game_dir);
+        let types = inheritance::GameTypes::new(data.join("Managed"));
 
         let build_settings = env.build_settings()?;
         let scene_names = build_settings
@@ -112,10 +300,30 @@ impl UniScan {
             env,
             scene_names,
             query,
+            index,
             cancel: Arc::new(AtomicBool::new(false)),
+            extra_files: Vec::new(),
+            enum_names: inheritance::EnumNames::Off,
+            with_base_types: false,
+            types,
         })
     }
 
+    /// Scan a file or all files in a directory in addition to the game. Returns the number of
+    /// newly added serialized files.
+    pub fn add_extra_files(&mut self, path: &Path) -> Result<usize> {
+        let mut added = 0;
+        for file in extra_files::expand(path)? {
+            for serialized in extra_files::serialized_paths(&file)? {
+                if !self.extra_files.contains(&serialized) {
+                    self.extra_files.push(serialized);
+                    added += 1;
+                }
+            }
+        }
+        Ok(added)
+    }
+
     pub fn collect_files(&self) -> Result<Vec<PathBuf>, anyhow::Error> {
         let mut files = self.env.game_files.serialized_files()?;
         if let Some(aa) = self.env.addressables()? {
@@ -126,9 +334,179 @@ impl UniScan {
                     .map(|cab| PathBuf::from(ArchivePath::same(cab))),
             );
         }
+        files.extend(self.extra_files.iter().cloned());
         Ok(files)
     }
 
+    /// The files passing `filter`, decided from their paths alone without loading them.
+    pub fn filter_files(&self, files: Vec<PathBuf>, filter: &FileFilter) -> Vec<PathBuf> {
+        if filter.is_empty() {
+            return files;
+        }
+        files
+            .into_iter()
+            .filter(|path| {
+                let path_str = format_path(path);
+                let cab = ArchivePath::try_parse(path).ok().flatten();
+                let bundle = cab
+                    .as_ref()
+                    .and_then(|cab| catalog_bundle(&self.env, cab.bundle));
+
+                if filter.exclude_bundles && cab.is_some() {
+                    return false;
+                }
+                if filter.only_addressables && bundle.is_none() {
+                    return false;
+                }
+                let name_matches = match &cab {
+                    Some(cab) => {
+                        let bundle = bundle.as_deref().unwrap_or(cab.bundle);
+                        filter.matches_name(catalog::file_name(bundle))
+                            || filter.matches_name(cab.file)
+                    }
+                    None => filter.matches_name(catalog::file_name(&path_str)),
+                };
+                if !name_matches {
+                    return false;
+                }
+                filter.scenes.is_empty()
+                    || self
+                        .file_scene(path)
+                        .is_some_and(|scene| filter.scenes.contains(&scene))
+            })
+            .collect()
+    }
+
+    /// The types a script derives from or implements, see [`inheritance::TypeHierarchy`].
+    pub fn base_types(&self, full_name: &str) -> Vec<String> {
+        self.types.get().base_types(full_name)
+    }
+
+    /// Whether a script passes the filter, reading the game's types only for `is:` terms.
+    pub fn script_matches(&self, script_filter: &ScriptFilter, script: &MonoScript) -> bool {
+        self.script_matches_in(script_filter, &script.assembly_name(), &script.full_name())
+    }
+
+    /// Like [`UniScan::script_matches`], for when only the script's names are known.
+    pub fn script_matches_in(
+        &self,
+        script_filter: &ScriptFilter,
+        assembly: &str,
+        full_name: &str,
+    ) -> bool {
+        let types = match script_filter.needs_base_types() {
+            true => self.types.get(),
+            false => &inheritance::TypeHierarchy::default(),
+        };
+        script_filter.matches_in(assembly, full_name, types)
+    }
+
+    /// Find all distinct `MonoScript`s matching the filter, by looking at the script objects
+    /// themselves rather than at their MonoBehaviour instances.
+    pub fn find_scripts(&self, script_filter: &ScriptFilter) -> Result<Vec<MonoScript>> {
+        let mut scripts = par_fold_reduce::<Vec<_>, _>(self.collect_files()?, |acc, path| {
+            let path_str = format_path(&path);
+            let file = self
+                .env
+                .load_serialized(&path_str)
+                .with_context(|| format!("Could not load '{path_str}'"))?;
+            for script in file.objects_of::<MonoScript>() {
+                let script = script.read()?;
+                if self.script_matches(script_filter, &script) {
+                    acc.push(script);
+                }
+            }
+            Ok(())
+        })?;
+        scripts.sort_by(|a, b| {
+            (a.assembly_name(), a.full_name()).cmp(&(b.assembly_name(), b.full_name()))
+        });
+        scripts.dedup_by(|a, b| {
+            a.assembly_name() == b.assembly_name() && a.full_name() == b.full_name()
+        });
+        Ok(scripts)
+    }
+
+    /// The serialized file of a build scene (`levelN`), by name as listed in the build settings.
+    pub fn scene_file(&self, scene: &str) -> Option<String> {
+        let index = self.scene_names.iter().position(|name| name == scene)?;
+        Some(format!("level{index}"))
+    }
+
+    /// The scene the objects of a file belong to, see [`file_scene`].
+    pub fn file_scene(&self, path: &Path) -> Option<String> {
+        file_scene(
+            &self.env,
+            &self.index,
+            &format_path(path),
+            Some(&self.scene_names),
+        )
+    }
+
+    /// The GameObject tree of a build scene, see [`hierarchy::scene_hierarchy`].
+    pub fn scene_hierarchy(&self, scene: &str) -> Result<Vec<hierarchy::SceneNode>> {
+        let path = self
+            .scene_file(scene)
+            .with_context(|| format!("'{scene}' is not a scene in the build settings"))?;
+        let file = self
+            .env
+            .load_serialized(&path)
+            .with_context(|| format!("Could not load '{path}'"))?;
+        hierarchy::scene_hierarchy(&file)
+    }
+
+    /// A GameObject of a scene hierarchy and its components, each read like [`query::deref`] does.
+    /// Components that can't be read are `{path_id, _error}`.
+    pub fn scene_node_value(
+        &self,
+        scene: &str,
+        node: &hierarchy::SceneNode,
+    ) -> Result<jaq_json::Val> {
+        let file = self
+            .scene_file(scene)
+            .with_context(|| format!("'{scene}' is not a scene in the build settings"))?;
+        let deref = |path_id| {
+            let pptr = qualify_pptr::QualifiedPPtr {
+                file: file.clone(),
+                path_id,
+            };
+            query::deref(&self.env, &self.index, pptr.to_val())
+        };
+
+        let mut obj = match deref(node.game_object)? {
+            jaq_json::Val::Obj(obj) => Rc::unwrap_or_clone(obj),
+            other => return Ok(other),
+        };
+        let components = node
+            .components
+            .iter()
+            .map(|component| {
+                deref(component.path_id).unwrap_or_else(|e| {
+                    let mut error = jaq_json::Map::default();
+                    error.insert(
+                        "path_id".to_string().into(),
+                        (component.path_id as isize).into(),
+                    );
+                    error.insert("_error".to_string().into(), format!("{e:#}").into());
+                    jaq_json::Val::obj(error)
+                })
+            })
+            .collect();
+        obj.insert("_components".to_string().into(), components);
+        Ok(jaq_json::Val::obj(obj))
+    }
+
+    /// An overview of the game, see [`info::GameInfo`].
+    pub fn info(&self) -> Result<info::GameInfo> {
+        info::GameInfo::from_env(&self.env, self.scene_names.clone())
+    }
+
+    /// The resolved typetree of a script as described by [`schema::typetree_schema`].
+    pub fn schema(&self, script: &MonoScript) -> Result<jaq_json::Val> {
+        let tt = schema::script_typetree(&self.env, &script.assembly_name(), &script.full_name())?;
+        Ok(schema::typetree_schema(&tt))
+    }
+
     pub fn scan_all(&self, script_filter: &ScriptFilter, limit: usize) -> Result<ScanResults> {
         self.scan_all_files(script_filter, limit, self.collect_files()?, &|_| {})
     }
@@ -182,9 +560,9 @@ impl UniScan {
                         return Ok(());
                     }
                 };
-                self.enrich_object(&path_str, file, script, &mut data)?;
+                self.enrich_object(&path_str, file, mb.path_id(), script, &mut data)?;
 
-                let query_result = self.query.exec(&self.env, data)?;
+                let query_result = self.query.exec(&self.env, &self.index, &self.types, data)?;
                 query_count.fetch_add(query_result.len(), Ordering::SeqCst);
 
                 for value in query_result {
@@ -233,7 +611,7 @@ impl UniScan {
                 continue;
             };
 
-            if script_filter.matches(&script) {
+            if self.script_matches(script_filter, &script) {
                 emit(&file, &script, mb)?;
             }
         }
@@ -246,11 +624,36 @@ impl UniScan {
         &self,
         path_str: &str,
         file: &SerializedFileHandle<'_>,
+        path_id: PathId,
         script: &MonoScript,
         data: &mut jaq_json::Val,
     ) -> Result<(), anyhow::Error> {
         qualify_pptr::qualify_pptrs(path_str, file, data)?;
-        enrich_object(data, path_str, file, Some(script), Some(&self.scene_names))?;
+        enrich_object(
+            data,
+            path_str,
+            file,
+            path_id,
+            Some(script),
+            Some(&self.scene_names),
+            &self.index,
+        )?;
+        let full_name = script.full_name();
+        // Reading the assemblies is slow, only do it when something will look at the types
+        if self.enum_names != inheritance::EnumNames::Off {
+            self.types
+                .get()
+                .name_enums(&full_name, data, self.enum_names);
+        }
+        if self.with_base_types
+            && let jaq_json::Val::Obj(obj) = data
+        {
+            let base_types = self.base_types(&full_name);
+            Rc::make_mut(obj).insert(
+                "_base_types".to_string().into(),
+                base_types.into_iter().map(jaq_json::Val::from).collect(),
+            );
+        }
         Ok(())
     }
 }
@@ -259,10 +662,13 @@ pub(crate) fn enrich_object<R: EnvResolver, P: TypeTreeProvider>(
     data: &mut jaq_json::Val,
     path_str: &str,
     file: &SerializedFileHandle<'_, R, P>,
+    path_id: PathId,
     script: Option<&MonoScript>,
     scene_names: Option<&[String]>,
+    index: &asset_index::AssetIndex,
 ) -> Result<(), anyhow::Error> {
     qualify_pptr::qualify_pptrs(path_str, file, data)?;
+    managed_refs::resolve_managed_references(data);
 
     let mut data_obj = match std::mem::take(data) {
         jaq_json::Val::Obj(obj) => Rc::into_inner(obj).expect("references hanging around"),
@@ -281,11 +687,20 @@ pub(crate) fn enrich_object<R: EnvResolver, P: TypeTreeProvider>(
         );
     }
 
-    if let Some(cab) = ArchivePath::try_parse(Path::new(path_str))? {
-        if let Ok(Some(aa)) = file.env.addressables() {
-            let bundle = aa.cab_to_bundle.get(cab.bundle).unwrap();
+    let asset_path = index.asset_path(file, path_str, path_id);
+    if let Some(asset_path) = &asset_path {
+        data_obj.insert("_asset_path".to_string().into(), asset_path.clone().into());
+    }
 
-            let mut formatted = format_path(bundle);
+    if let Some(cab) = ArchivePath::try_parse(Path::new(path_str))? {
+        // Loose bundles added as extra files aren't part of the catalog
+        let bundle = catalog_bundle(file.env, cab.bundle);
+        if let (Some(bundle), Some(asset_path)) = (&bundle, &asset_path)
+            && let Some(asset) = index.addressable_asset(catalog::file_name(bundle), asset_path)
+        {
+            insert_addressable_fields(&mut data_obj, asset);
+        }
+        if let Some(mut formatted) = bundle.or_else(|| extra_files_bundle(cab.bundle)) {
             if cab.bundle != cab.file {
                 let _ = write!(&mut formatted, " ({})", cab.file);
             }
@@ -296,20 +711,73 @@ pub(crate) fn enrich_object<R: EnvResolver, P: TypeTreeProvider>(
         data_obj.insert("_file".to_string().into(), path_str.to_owned().into());
     }
 
-    if let Some(scene_names) = scene_names
-        && let Some(scene_index) = path_str
-            .strip_prefix("level")
-            .and_then(|x| x.parse::<usize>().ok())
-    {
-        let scene_name = &scene_names[scene_index];
-        data_obj.insert("_scene".to_string().into(), scene_name.clone().into());
+    if let Some(scene_name) = file_scene(file.env, index, path_str, scene_names) {
+        data_obj.insert("_scene".to_string().into(), scene_name.into());
     }
 
     *data = jaq_json::Val::obj(data_obj);
     Ok(())
 }
 
-fn format_path(path: &Path) -> String {
+fn insert_addressable_fields(obj: &mut jaq_json::Map, asset: &catalog::AddressableAsset) {
+    let strings = |items: &[String]| -> jaq_json::Val {
+        items.iter().cloned().map(jaq_json::Val::from).collect()
+    };
+    obj.insert("_address".to_string().into(), asset.address.clone().into());
+    obj.insert("_labels".to_string().into(), strings(&asset.labels));
+    obj.insert(
+        "_bundle_deps".to_string().into(),
+        strings(&asset.bundle_deps),
+    );
+}
+
+/// The scene a file belongs to: build scene N for `levelN` and `sharedassetsN.assets`, or the
+/// scene of an addressable scene bundle.
+fn file_scene<R: EnvResolver, P: TypeTreeProvider>(
+    env: &Environment<R, P>,
+    index: &asset_index::AssetIndex,
+    path_str: &str,
+    scene_names: Option<&[String]>,
+) -> Option<String> {
+    if let Some(cab) = ArchivePath::try_parse(Path::new(path_str)).ok().flatten() {
+        let bundle = catalog_bundle(env, cab.bundle)?;
+        return index
+            .bundle_scene(catalog::file_name(&bundle))
+            .map(ToOwned::to_owned);
+    }
+    let scene_index = build_scene_index(path_str)?;
+    scene_names?.get(scene_index).cloned()
+}
+
+/// The build scene index of `levelN` and `sharedassetsN.assets`.
+fn build_scene_index(path_str: &str) -> Option<usize> {
+    let index = path_str.strip_prefix("level").or_else(|| {
+        path_str
+            .strip_prefix("sharedassets")?
+            .strip_suffix(".assets")
+    })?;
+    index.parse().ok()
+}
+
+/// The path of the addressable bundle containing the serialized file `cab`.
+fn catalog_bundle<R: EnvResolver, P: TypeTreeProvider>(
+    env: &Environment<R, P>,
+    cab: &str,
+) -> Option<String> {
+    match env.addressables() {
+        Ok(Some(aa)) => aa.cab_to_bundle.get(cab).map(|bundle| format_path(bundle)),
+        _ => None,
+    }
+}
+
+/// The bundle of an extra file entry is its path on disk.
+fn extra_files_bundle(bundle: &str) -> Option<String> {
+    Path::new(bundle)
+        .is_absolute()
+        .then(|| format_path(Path::new(bundle)))
+}
+
+pub(crate) fn format_path(path: &Path) -> String {
     let formatted = path.display().to_string();
     #[cfg(not(target_os = "windows"))]
     return formatted;
@@ -319,7 +787,8 @@ fn format_path(path: &Path) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::ScriptFilter;
+    use super::{ScriptFilter, build_scene_index};
+    use crate::inheritance::TypeHierarchy;
     use rabex_env::unity::types::MonoScript;
 
     fn script(namespace: &str, class: &str) -> MonoScript {
@@ -333,23 +802,113 @@ mod tests {
         }
     }
 
+    /// For filters without `is:` terms, which don't look at the types.
+    fn no_types() -> TypeHierarchy {
+        TypeHierarchy::default()
+    }
+
     #[test]
     fn empty_filter_matches_everything() {
-        assert!(ScriptFilter::empty().matches(&script("", "HeroController")));
+        assert!(ScriptFilter::empty().matches(&script("", "HeroController"), &no_types()));
     }
 
     #[test]
     fn matches_case_insensitive_substring_of_full_name() {
         let s = script("Game.Enemies", "HeroController");
-        assert!(ScriptFilter::new("hero").matches(&s));
-        assert!(ScriptFilter::new("HERO").matches(&s));
+        assert!(ScriptFilter::new("hero").matches(&s, &no_types()));
+        assert!(ScriptFilter::new("HERO").matches(&s, &no_types()));
         // the filter runs against the namespaced full name
-        assert!(ScriptFilter::new("game.enemies.herocontroller").matches(&s));
+        assert!(ScriptFilter::new("game.enemies.herocontroller").matches(&s, &no_types()));
     }
 
     #[test]
     fn rejects_non_substring() {
-        assert!(!ScriptFilter::new("villain").matches(&script("", "HeroController")));
+        assert!(!ScriptFilter::new("villain").matches(&script("", "HeroController"), &no_types()));
+    }
+
+    #[test]
+    fn exact_matches_class_or_full_name() {
+        let enemy = script("Game", "Enemy");
+        let effects = script("Game", "EnemyDeathEffects");
+        assert!(ScriptFilter::new("=Enemy").matches(&enemy, &no_types()));
+        assert!(ScriptFilter::new("=Game.Enemy").matches(&enemy, &no_types()));
+        assert!(!ScriptFilter::new("=Enemy").matches(&effects, &no_types()));
+        assert!(!ScriptFilter::new("=enemy").matches(&enemy, &no_types()));
+    }
+
+    #[test]
+    fn regex_matches_full_name() {
+        let filter = ScriptFilter::new("/^Game\\.Enemy$/");
+        assert!(filter.matches(&script("Game", "Enemy"), &no_types()));
+        assert!(!filter.matches(&script("Game", "EnemyDeathEffects"), &no_types()));
+        assert!(!ScriptFilter::new("/(/").matches(&script("", "("), &no_types()));
+        assert!(ScriptFilter::try_new("/(/").is_err());
+        assert!(ScriptFilter::try_new("(").is_ok());
+    }
+
+    #[test]
+    fn namespace_and_assembly_prefixes() {
+        let s = script("Game.Enemies", "Crawler");
+        assert!(ScriptFilter::new("ns:enemies").matches(&s, &no_types()));
+        assert!(!ScriptFilter::new("ns:=Game").matches(&s, &no_types()));
+        assert!(ScriptFilter::new("ns:=Game.Enemies").matches(&s, &no_types()));
+        assert!(ScriptFilter::new("asm:=Assembly-CSharp").matches(&s, &no_types()));
+        assert!(!ScriptFilter::new("asm:firstpass").matches(&s, &no_types()));
+        // the assembly is unknown when matching by name only
+        assert!(
+            ScriptFilter::new("asm:firstpass").matches_name("Game.Enemies.Crawler", &no_types())
+        );
+        assert!(ScriptFilter::new("asm:firstpass").matches_in(
+            "Assembly-CSharp-firstpass.dll",
+            "Crawler",
+            &no_types()
+        ));
+    }
+
+    #[test]
+    fn base_types_match_with_is() {
+        let types = TypeHierarchy::from_bases(
+            [
+                ("Game.Crawler", vec!["Game.Enemy"]),
+                ("Game.Enemy", vec!["UnityEngine.MonoBehaviour"]),
+                ("Game.Player", vec!["UnityEngine.MonoBehaviour"]),
+            ]
+            .into_iter()
+            .map(|(name, bases)| (name.into(), bases.into_iter().map(Into::into).collect()))
+            .collect(),
+        );
+        let crawler = script("Game", "Crawler");
+        let filter = ScriptFilter::new("is:=Enemy");
+        assert!(filter.needs_base_types());
+        assert!(filter.matches(&crawler, &types));
+        assert!(!filter.matches(&script("Game", "Player"), &types));
+        // a script is-a itself
+        assert!(ScriptFilter::new("is:=Game.Crawler").matches(&crawler, &TypeHierarchy::default()));
+        assert!(!ScriptFilter::new("!is:enemy").matches(&crawler, &types));
+        assert!(!ScriptFilter::new("crawler").needs_base_types());
+    }
+
+    #[test]
+    fn alternatives_and_negation() {
+        let filter = ScriptFilter::new("enemy, boss, !effects");
+        assert!(filter.matches(&script("", "EnemyAI"), &no_types()));
+        assert!(filter.matches(&script("", "BossController"), &no_types()));
+        assert!(!filter.matches(&script("", "EnemyDeathEffects"), &no_types()));
+        assert!(!filter.matches(&script("", "Player"), &no_types()));
+
+        let only_negated = ScriptFilter::new("!ns:UnityEngine");
+        assert!(only_negated.matches(&script("Game", "Player"), &no_types()));
+        assert!(!only_negated.matches(&script("UnityEngine.UI", "Button"), &no_types()));
+    }
+
+    #[test]
+    fn levels_and_sharedassets_map_to_build_scenes() {
+        assert_eq!(build_scene_index("level0"), Some(0));
+        assert_eq!(build_scene_index("level12"), Some(12));
+        assert_eq!(build_scene_index("sharedassets3.assets"), Some(3));
+        assert_eq!(build_scene_index("sharedassets3.assets.resS"), None);
+        assert_eq!(build_scene_index("resources.assets"), None);
+        assert_eq!(build_scene_index("globalgamemanagers"), None);
     }
 }
 
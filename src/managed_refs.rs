@@ -0,0 +1,245 @@
+//! Resolution of `[SerializeReference]` fields.
+//!
+//! Objects with managed references store them in a registry, `references.RefIds`, as
+//! `{rid, type: {class, ns, asm}, data}`. The fields themselves only hold `{rid}`, which is
+//! replaced here by the referenced data, tagged with its concrete `_type` and `_asm`.
+
+use std::collections::{HashMap, HashSet};
+
+use jaq_json::{Rc, Val};
+use jaq_std::ValT as _;
+
+use crate::hierarchy::field;
+
+/// Rids of null references (`-2` since registry version 2, `-1` before).
+const NULL_RIDS: [isize; 2] = [-1, -2];
+
+struct Reference {
+    full_name: String,
+    assembly: String,
+    data: Val,
+}
+
+/// Inline the managed references of an object at their use sites and drop the registry.
+///
+/// A reference used in several places is inlined at the first one and kept as
+/// `{rid, _type, _asm, _ref: true}` at the others, so shared references don't multiply. A
+/// reference reached again while it is being inlined, i.e. a cycle, is kept as
+/// `{rid, _type, _asm, _cycle: true}`.
+pub fn resolve_managed_references(value: &mut Val) {
+    // A script's own field can be called `references` too
+    let Some(registry) = field(value, "references")
+        .filter(|registry| matches!(field(registry, "RefIds"), Some(Val::Arr(_))))
+    else {
+        return;
+    };
+    let references = read_registry(registry);
+    let Val::Obj(map) = value else {
+        return;
+    };
+    let map = Rc::make_mut(map);
+    map.retain(|key, _| key.as_utf8_bytes() != Some(b"references"));
+    if references.is_empty() {
+        return;
+    }
+
+    let mut inliner = Inliner {
+        references: &references,
+        stack: Vec::new(),
+        inlined: HashSet::new(),
+    };
+    for field_value in map.values_mut() {
+        inliner.inline(field_value);
+    }
+}
+
+fn read_registry(registry: &Val) -> HashMap<isize, Reference> {
+    let Some(Val::Arr(ref_ids)) = field(registry, "RefIds") else {
+        return HashMap::new();
+    };
+    ref_ids
+        .iter()
+        .filter_map(|entry| {
+            let rid = field(entry, "rid")?.as_isize()?;
+            let ty = field(entry, "type")?;
+            let string = |name: &str| {
+                let bytes = field(ty, name)
+                    .and_then(|v| v.as_utf8_bytes())
+                    .unwrap_or_default();
+                String::from_utf8_lossy(bytes).into_owned()
+            };
+            let (class, namespace) = (string("class"), string("ns"));
+            let full_name = match namespace.is_empty() {
+                true => class,
+                false => format!("{namespace}.{class}"),
+            };
+            let reference = Reference {
+                full_name,
+                assembly: string("asm"),
+                data: field(entry, "data").cloned().unwrap_or(Val::Null),
+            };
+            Some((rid, reference))
+        })
+        .collect()
+}
+
+struct Inliner<'a> {
+    references: &'a HashMap<isize, Reference>,
+    /// The references being inlined, outermost first.
+    stack: Vec<isize>,
+    /// The references inlined so far, at their first use site.
+    inlined: HashSet<isize>,
+}
+
+impl Inliner<'_> {
+    fn inline(&mut self, value: &mut Val) {
+        if let Some(rid) = use_site_rid(value) {
+            *value = self.resolve(rid);
+            return;
+        }
+        match value {
+            Val::Arr(values) => Rc::make_mut(values)
+                .iter_mut()
+                .for_each(|value| self.inline(value)),
+            Val::Obj(map) => Rc::make_mut(map)
+                .values_mut()
+                .for_each(|value| self.inline(value)),
+            _ => {}
+        }
+    }
+
+    fn resolve(&mut self, rid: isize) -> Val {
+        let Some(reference) = self.references.get(&rid) else {
+            return match NULL_RIDS.contains(&rid) {
+                true => Val::Null,
+                false => rid_object(rid, Vec::new()),
+            };
+        };
+        let mut tags = vec![
+            ("_type", Val::from(reference.full_name.clone())),
+            ("_asm", Val::from(reference.assembly.clone())),
+        ];
+        if self.stack.contains(&rid) {
+            tags.push(("_cycle", Val::from(true)));
+            return rid_object(rid, tags);
+        }
+        if !self.inlined.insert(rid) {
+            tags.push(("_ref", Val::from(true)));
+            return rid_object(rid, tags);
+        }
+
+        let mut data = reference.data.clone();
+        self.stack.push(rid);
+        self.inline(&mut data);
+        self.stack.pop();
+
+        match data {
+            Val::Obj(map) => {
+                let mut map = Rc::unwrap_or_clone(map);
+                for (key, tag) in tags {
+                    map.insert(key.to_string().into(), tag);
+                }
+                Val::obj(map)
+            }
+            // Data of primitive types, e.g. a boxed `int`
+            other => {
+                tags.push(("value", other));
+                rid_object(rid, tags)
+            }
+        }
+    }
+}
+
+/// The rid of a `{rid}` field holding a managed reference.
+fn use_site_rid(value: &Val) -> Option<isize> {
+    match value {
+        Val::Obj(map) if map.len() == 1 => field(value, "rid")?.as_isize(),
+        _ => None,
+    }
+}
+
+fn rid_object(rid: isize, fields: Vec<(&str, Val)>) -> Val {
+    let mut map = jaq_json::Map::default();
+    map.insert("rid".to_string().into(), rid.into());
+    for (key, value) in fields {
+        map.insert(key.to_string().into(), value);
+    }
+    Val::obj(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_managed_references;
+    use jaq_json::Val;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    fn resolved(s: &str) -> Val {
+        let mut value = val(s);
+        resolve_managed_references(&mut value);
+        value
+    }
+
+    const REGISTRY: &str = r#""references": {"version": 2, "RefIds": [
+        {"rid": 1, "type": {"class": "Poison", "ns": "Game.Effects", "asm": "Assembly-CSharp"},
+         "data": {"damage": 3, "next": {"rid": 2}}},
+        {"rid": 2, "type": {"class": "Slow", "ns": "", "asm": "Assembly-CSharp"},
+         "data": {"factor": 0.5}},
+        {"rid": 3, "type": {"class": "Loop", "ns": "", "asm": "Assembly-CSharp"},
+         "data": {"self": {"rid": 3}}}
+    ]}"#;
+
+    #[test]
+    fn inlines_nested_references_with_their_type() {
+        let value = resolved(&format!(r#"{{"effect": {{"rid": 1}}, {REGISTRY}}}"#));
+        assert_eq!(
+            value,
+            val(r#"{"effect": {
+                "damage": 3,
+                "next": {"factor": 0.5, "_type": "Slow", "_asm": "Assembly-CSharp"},
+                "_type": "Game.Effects.Poison", "_asm": "Assembly-CSharp"
+            }}"#)
+        );
+    }
+
+    #[test]
+    fn shared_references_are_inlined_once_and_nulls_resolved() {
+        let value = resolved(&format!(
+            r#"{{"effects": [{{"rid": 2}}, {{"rid": 2}}, {{"rid": -2}}], {REGISTRY}}}"#
+        ));
+        assert_eq!(
+            value,
+            val(r#"{"effects": [
+                {"factor": 0.5, "_type": "Slow", "_asm": "Assembly-CSharp"},
+                {"rid": 2, "_type": "Slow", "_asm": "Assembly-CSharp", "_ref": true},
+                null
+            ]}"#)
+        );
+    }
+
+    #[test]
+    fn cycles_are_cut() {
+        let value = resolved(&format!(r#"{{"loop": {{"rid": 3}}, {REGISTRY}}}"#));
+        assert_eq!(
+            value,
+            val(r#"{"loop": {
+                "self": {"rid": 3, "_type": "Loop", "_asm": "Assembly-CSharp", "_cycle": true},
+                "_type": "Loop", "_asm": "Assembly-CSharp"
+            }}"#)
+        );
+    }
+
+    #[test]
+    fn objects_without_references_are_unchanged() {
+        let object = r#"{"rid": 5, "other": {"rid": 1}}"#;
+        assert_eq!(resolved(object), val(object));
+    }
+
+    #[test]
+    fn plain_references_fields_are_kept() {
+        let object = r#"{"references": [{"m_FileID": 0, "m_PathID": 4}], "other": {"rid": 1}}"#;
+        assert_eq!(resolved(object), val(object));
+    }
+}
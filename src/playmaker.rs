@@ -0,0 +1,474 @@
+//! Decoding of PlayMakerFSM components into a state graph.
+//!
+//! A serialized FSM stores the parameters of all actions of a state in one `actionData` blob:
+//! per parameter a name, a [`ParamDataType`](PARAM_DATA_TYPES) and a position, either into one of
+//! the typed `fsm*Params` lists or into `byteData` for plain values.
+
+use std::fmt::Write as _;
+
+use anyhow::{Context as _, Result};
+use jaq_json::Val;
+use jaq_std::ValT as _;
+
+use crate::hierarchy::field;
+
+/// PlayMaker's `ParamDataType`, in declaration order.
+const PARAM_DATA_TYPES: [&str; 43] = [
+    "Integer",
+    "Boolean",
+    "Float",
+    "String",
+    "Color",
+    "ObjectReference",
+    "LayerMask",
+    "Enum",
+    "Vector2",
+    "Vector3",
+    "Vector4",
+    "Rect",
+    "Array",
+    "Character",
+    "AnimationCurve",
+    "FsmFloat",
+    "FsmInt",
+    "FsmBool",
+    "FsmString",
+    "FsmGameObject",
+    "FsmOwnerDefault",
+    "FunctionCall",
+    "FsmAnimationCurve",
+    "FsmEvent",
+    "FsmObject",
+    "FsmColor",
+    "Unsupported",
+    "GameObject",
+    "FsmVector3",
+    "LayoutOption",
+    "FsmRect",
+    "FsmEventTarget",
+    "FsmMaterial",
+    "FsmTexture",
+    "Quaternion",
+    "FsmQuaternion",
+    "FsmProperty",
+    "FsmVector2",
+    "FsmTemplateControl",
+    "FsmVar",
+    "CustomClass",
+    "FsmArray",
+    "FsmEnum",
+];
+
+/// Decode a PlayMakerFSM component, or its `fsm` field, into
+/// `{name, start_state, events, global_transitions, states}`, where each state is
+/// `{name, description, transitions: [{event, to}], actions: [{type, name, enabled, params}]}`.
+///
+/// Of the component's other fields only the `_`-prefixed ones added by the scan, like `_file`,
+/// are kept.
+pub fn fsm_graph(component: &Val) -> Result<Val> {
+    let fsm = field(component, "fsm").unwrap_or(component);
+    let states = array(fsm, "states").context("not a PlayMakerFSM, it has no `fsm.states`")?;
+
+    let events = array(fsm, "events")
+        .unwrap_or_default()
+        .iter()
+        .map(|event| {
+            object([
+                ("name", string_field(event, "name").into()),
+                ("global", bool_field(event, "isGlobal").into()),
+            ])
+        })
+        .collect();
+    let states = states
+        .iter()
+        .map(|state| {
+            let actions = field(state, "actionData").map_or(Vec::new(), actions);
+            object([
+                ("name", string_field(state, "name").into()),
+                ("description", string_field(state, "description").into()),
+                ("transitions", transitions(state, "transitions")),
+                ("actions", actions.into_iter().collect()),
+            ])
+        })
+        .collect();
+
+    let mut graph = match component {
+        Val::Obj(map) => {
+            let mut map = (**map).clone();
+            map.retain(|key, _| key.as_utf8_bytes().is_some_and(|k| k.starts_with(b"_")));
+            map
+        }
+        _ => jaq_json::Map::default(),
+    };
+    graph.insert("name".to_string().into(), string_field(fsm, "name").into());
+    graph.insert(
+        "start_state".to_string().into(),
+        string_field(fsm, "startState").into(),
+    );
+    graph.insert("events".to_string().into(), events);
+    graph.insert(
+        "global_transitions".to_string().into(),
+        transitions(fsm, "globalTransitions"),
+    );
+    graph.insert("states".to_string().into(), states);
+    Ok(Val::obj(graph))
+}
+
+/// Render a graph from [`fsm_graph`] in Graphviz DOT. States are boxes listing their actions,
+/// transitions are edges labelled with their event, global transitions are dashed.
+pub fn to_dot(graph: &Val) -> String {
+    let mut dot = String::new();
+    let _ = writeln!(dot, "digraph {} {{", quote(&graph_label(graph)));
+    dot.push_str("  node [shape=box];\n");
+
+    let start = string_field(graph, "start_state");
+    if !start.is_empty() {
+        dot.push_str("  \"\" [shape=point];\n");
+        let _ = writeln!(dot, "  \"\" -> {};", quote(&start));
+    }
+    for state in array(graph, "states").unwrap_or_default() {
+        let name = string_field(state, "name");
+        let mut label = name.clone();
+        for action in array(state, "actions").unwrap_or_default() {
+            let ty = string_field(action, "type");
+            let short = ty.rsplit('.').next().unwrap_or_default();
+            label.push('\n');
+            label.push_str(short);
+        }
+        let _ = writeln!(dot, "  {} [label={}];", quote(&name), quote(&label));
+        for transition in array(state, "transitions").unwrap_or_default() {
+            let _ = writeln!(
+                dot,
+                "  {} -> {} [label={}];",
+                quote(&name),
+                quote(&string_field(transition, "to")),
+                quote(&string_field(transition, "event")),
+            );
+        }
+    }
+    for transition in array(graph, "global_transitions").unwrap_or_default() {
+        let event = string_field(transition, "event");
+        let source = format!("global {event}");
+        let _ = writeln!(
+            dot,
+            "  {} [shape=plaintext, label={}];",
+            quote(&source),
+            quote(&event)
+        );
+        let _ = writeln!(
+            dot,
+            "  {} -> {} [style=dashed];",
+            quote(&source),
+            quote(&string_field(transition, "to")),
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// `<game_object>: <fsm name>`, or just the FSM's name if the graph has no `game_object`.
+pub fn graph_label(graph: &Val) -> String {
+    let name = string_field(graph, "name");
+    match string_field(graph, "game_object").as_str() {
+        "" => name,
+        game_object => format!("{game_object}: {name}"),
+    }
+}
+
+fn transitions(value: &Val, name: &str) -> Val {
+    array(value, name)
+        .unwrap_or_default()
+        .iter()
+        .map(|transition| {
+            let event = field(transition, "fsmEvent")
+                .map_or(String::new(), |event| string_field(event, "name"));
+            object([
+                ("event", event.into()),
+                ("to", string_field(transition, "toState").into()),
+            ])
+        })
+        .collect()
+}
+
+/// The actions of a state, from its `actionData`.
+fn actions(data: &Val) -> Vec<Val> {
+    let names = array(data, "actionNames").unwrap_or_default();
+    let custom_names = array(data, "customNames").unwrap_or_default();
+    let enabled = array(data, "actionEnabled").unwrap_or_default();
+    let starts: Vec<usize> = ints(data, "actionStartIndex");
+    let param_names = array(data, "paramName").unwrap_or_default();
+    let param_types = ints(data, "paramDataType");
+    let param_positions = ints(data, "paramDataPos");
+    let param_sizes = ints(data, "paramByteDataSize");
+    let byte_data = bytes(field(data, "byteData"));
+
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let start = starts.get(i).copied().unwrap_or(param_names.len());
+            let end = starts.get(i + 1).copied().unwrap_or(param_names.len());
+            let mut params = jaq_json::Map::default();
+            for param in start..end.min(param_names.len()) {
+                let ty = param_types.get(param).copied().unwrap_or(usize::MAX);
+                let position = param_positions.get(param).copied().unwrap_or_default();
+                let size = param_sizes.get(param).copied().unwrap_or_default();
+                let value = param_value(data, &byte_data, ty, position, size);
+                let param_name = param_names[param].as_utf8_bytes().unwrap_or_default();
+                params.insert(
+                    String::from_utf8_lossy(param_name).into_owned().into(),
+                    value,
+                );
+            }
+
+            let custom_name = custom_names.get(i).map_or(String::new(), string);
+            object([
+                ("type", string(name).into()),
+                ("name", custom_name.into()),
+                ("enabled", enabled.get(i).is_none_or(|e| e.as_bool()).into()),
+                ("params", Val::obj(params)),
+            ])
+        })
+        .collect()
+}
+
+/// The value of an action parameter of type `ty`.
+fn param_value(data: &Val, byte_data: &[u8], ty: usize, position: usize, size: usize) -> Val {
+    let Some(&type_name) = PARAM_DATA_TYPES.get(ty) else {
+        return object([("unsupported", (ty as isize).into())]);
+    };
+    let list = match type_name {
+        "FsmFloat" => "fsmFloatParams",
+        "FsmInt" => "fsmIntParams",
+        "FsmBool" => "fsmBoolParams",
+        "FsmString" => "fsmStringParams",
+        "FsmGameObject" => "fsmGameObjectParams",
+        "FsmOwnerDefault" => "fsmOwnerDefaultParams",
+        "FunctionCall" => "functionCallParams",
+        "FsmAnimationCurve" | "AnimationCurve" => "animationCurveParams",
+        "FsmEvent" => "stringParams",
+        "FsmObject" | "FsmMaterial" | "FsmTexture" => "fsmObjectParams",
+        "FsmColor" => "fsmColorParams",
+        "FsmVector3" => "fsmVector3Params",
+        "FsmVector2" => "fsmVector2Params",
+        "FsmRect" => "fsmRectParams",
+        "FsmQuaternion" => "fsmQuaternionParams",
+        "LayoutOption" => "layoutOptionParams",
+        "FsmEventTarget" => "fsmEventTargetParams",
+        "FsmProperty" => "fsmPropertyParams",
+        "FsmTemplateControl" => "fsmTemplateControlParams",
+        "FsmVar" => "fsmVarParams",
+        "FsmArray" => "fsmArrayParams",
+        "FsmEnum" => "fsmEnumParams",
+        "ObjectReference" | "GameObject" => "unityObjectParams",
+        _ => return byte_value(type_name, byte_data.get(position..position + size)),
+    };
+    array(data, list)
+        .and_then(|list| list.get(position))
+        .map_or(Val::Null, fsm_value)
+}
+
+/// Plain values are stored little endian in `byteData`.
+fn byte_value(type_name: &str, bytes: Option<&[u8]>) -> Val {
+    let Some(bytes) = bytes else {
+        return Val::Null;
+    };
+    let i32_at = |i: usize| {
+        let bytes = bytes.get(i * 4..i * 4 + 4)?;
+        Some(i32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    let float = |i: usize| match i32_at(i) {
+        Some(bits) => Val::from(f32::from_bits(bits as u32) as f64),
+        None => Val::Null,
+    };
+    let floats = |n: usize| (0..n).map(float).collect();
+    match type_name {
+        "Integer" | "Enum" | "LayerMask" => i32_at(0).map_or(Val::Null, |v| (v as isize).into()),
+        "Boolean" => bytes.first().map_or(Val::Null, |&b| (b != 0).into()),
+        "Float" => float(0),
+        "String" => String::from_utf8_lossy(bytes).into_owned().into(),
+        "Vector2" => floats(2),
+        "Vector3" => floats(3),
+        "Vector4" | "Color" | "Rect" | "Quaternion" => floats(4),
+        _ => object([("unsupported", type_name.to_owned().into())]),
+    }
+}
+
+/// Named variables are shown as `{variable}`, constants as their value.
+fn fsm_value(value: &Val) -> Val {
+    let name = string_field(value, "name");
+    if bool_field(value, "useVariable") && !name.is_empty() {
+        return object([("variable", name.into())]);
+    }
+    match field(value, "value") {
+        Some(inner) => inner.clone(),
+        None => value.clone(),
+    }
+}
+
+fn object<const N: usize>(fields: [(&str, Val); N]) -> Val {
+    let mut map = jaq_json::Map::default();
+    for (key, value) in fields {
+        map.insert(key.to_string().into(), value);
+    }
+    Val::obj(map)
+}
+
+fn array<'a>(value: &'a Val, name: &str) -> Option<&'a [Val]> {
+    match field(value, name) {
+        Some(Val::Arr(values)) => Some(values.as_slice()),
+        _ => None,
+    }
+}
+
+fn ints(value: &Val, name: &str) -> Vec<usize> {
+    array(value, name)
+        .unwrap_or_default()
+        .iter()
+        .map(|v| v.as_isize().unwrap_or_default().max(0) as usize)
+        .collect()
+}
+
+/// A `vector<UInt8>`, read as a list of numbers.
+fn bytes(value: Option<&Val>) -> Vec<u8> {
+    match value {
+        Some(Val::Arr(values)) => values
+            .iter()
+            .map(|v| v.as_isize().unwrap_or_default() as u8)
+            .collect(),
+        Some(value) => value.as_utf8_bytes().unwrap_or_default().to_vec(),
+        None => Vec::new(),
+    }
+}
+
+fn string(value: &Val) -> String {
+    String::from_utf8_lossy(value.as_utf8_bytes().unwrap_or_default()).into_owned()
+}
+
+fn string_field(value: &Val, name: &str) -> String {
+    field(value, name).map_or(String::new(), string)
+}
+
+fn bool_field(value: &Val, name: &str) -> bool {
+    field(value, name).is_some_and(|v| v.as_bool())
+}
+
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fsm_graph, to_dot};
+    use crate::hierarchy::field;
+    use jaq_json::Val;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    const FSM: &str = r#"{"_file": "level1", "m_Enabled": 1, "fsm": {
+        "name": "Door Control", "startState": "Closed",
+        "events": [{"name": "OPEN", "isGlobal": false}, {"name": "RESET", "isGlobal": true}],
+        "globalTransitions": [{"fsmEvent": {"name": "RESET"}, "toState": "Closed"}],
+        "states": [
+            {"name": "Closed", "description": "",
+             "transitions": [{"fsmEvent": {"name": "OPEN"}, "toState": "Open"}],
+             "actionData": {
+                "actionNames": ["HutongGames.PlayMaker.Actions.Wait", "HutongGames.PlayMaker.Actions.SendEvent"],
+                "customNames": ["", "Notify"],
+                "actionEnabled": [true, false],
+                "actionStartIndex": [0, 2],
+                "paramName": ["time", "realTime", "sendEvent", "delay"],
+                "paramDataType": [15, 1, 23, 2],
+                "paramDataPos": [0, 0, 0, 1],
+                "paramByteDataSize": [0, 1, 0, 4],
+                "byteData": [1, 0, 0, 64],
+                "fsmFloatParams": [{"value": 1.5, "name": "Wait Time", "useVariable": true}],
+                "stringParams": ["OPEN"]
+             }},
+            {"name": "Open", "description": "The door is \"open\"", "transitions": []}
+        ]
+    }}"#;
+
+    #[test]
+    fn decodes_states_transitions_and_action_params() {
+        assert_eq!(
+            fsm_graph(&val(FSM)).unwrap(),
+            val(r#"{
+                "_file": "level1",
+                "name": "Door Control", "start_state": "Closed",
+                "events": [{"name": "OPEN", "global": false}, {"name": "RESET", "global": true}],
+                "global_transitions": [{"event": "RESET", "to": "Closed"}],
+                "states": [
+                    {"name": "Closed", "description": "",
+                     "transitions": [{"event": "OPEN", "to": "Open"}],
+                     "actions": [
+                        {"type": "HutongGames.PlayMaker.Actions.Wait", "name": "", "enabled": true,
+                         "params": {"time": {"variable": "Wait Time"}, "realTime": true}},
+                        {"type": "HutongGames.PlayMaker.Actions.SendEvent", "name": "Notify", "enabled": false,
+                         "params": {"sendEvent": "OPEN", "delay": null}}
+                     ]},
+                    {"name": "Open", "description": "The door is \"open\"", "transitions": [], "actions": []}
+                ]
+            }"#)
+        );
+    }
+
+    #[test]
+    fn plain_values_are_read_from_byte_data() {
+        let bytes = 2.5f32.to_le_bytes().map(|b| b.to_string()).join(", ");
+        let fsm = val(&format!(
+            r#"{{"name": "", "states": [{{"name": "A", "actionData": {{
+                "actionNames": ["Log"], "actionStartIndex": [0],
+                "paramName": ["speed", "count", "text"], "paramDataType": [2, 0, 3],
+                "paramDataPos": [0, 4, 8], "paramByteDataSize": [4, 4, 2],
+                "byteData": [{bytes}, 7, 0, 0, 0, 104, 105]
+            }}}}]}}"#
+        ));
+        let graph = fsm_graph(&fsm).unwrap();
+        assert_eq!(
+            *field(&graph, "states").unwrap(),
+            val(
+                r#"[{"name": "A", "description": "", "transitions": [], "actions": [
+                {"type": "Log", "name": "", "enabled": true,
+                 "params": {"speed": 2.5, "count": 7, "text": "hi"}}
+            ]}]"#
+            )
+        );
+    }
+
+    #[test]
+    fn other_objects_are_an_error() {
+        assert!(fsm_graph(&val(r#"{"m_Name": "Player"}"#)).is_err());
+    }
+
+    #[test]
+    fn dot_has_states_transitions_and_escaped_labels() {
+        let mut graph = fsm_graph(&val(FSM)).unwrap();
+        if let Val::Obj(map) = &mut graph {
+            jaq_json::Rc::make_mut(map).insert(
+                "game_object".to_string().into(),
+                r#"Level/"Door""#.to_string().into(),
+            );
+        }
+        assert_eq!(
+            to_dot(&graph),
+            r#"digraph "Level/\"Door\": Door Control" {
+  node [shape=box];
+  "" [shape=point];
+  "" -> "Closed";
+  "Closed" [label="Closed\nWait\nSendEvent"];
+  "Closed" -> "Open" [label="OPEN"];
+  "Open" [label="Open"];
+  "global RESET" [shape=plaintext, label="RESET"];
+  "global RESET" -> "Closed" [style=dashed];
+}
+"#
+        );
+    }
+}
@@ -13,6 +13,25 @@ pub struct QualifiedPPtr {
 }
 
 impl QualifiedPPtr {
+    pub fn to_val(&self) -> jaq_json::Val {
+        let mut obj = jaq_json::Map::default();
+        obj.insert("file".to_string().into(), self.file.clone().into());
+        obj.insert("path_id".to_string().into(), (self.path_id as isize).into());
+        jaq_json::Val::obj(obj)
+    }
+
+    /// Whether `v` looks like a PPtr produced by [`qualify_pptrs`], i.e. exactly
+    /// `{file, path_id, class_id}`.
+    pub fn is_qualified(v: &jaq_json::Val) -> bool {
+        let jaq_json::Val::Obj(map) = v else {
+            return false;
+        };
+        map.len() == 3
+            && [&b"file"[..], b"path_id", b"class_id"]
+                .iter()
+                .all(|name| map.iter().any(|(k, _)| k.as_utf8_bytes() == Some(name)))
+    }
+
     /// Extract a qualified PPtr from a `{file, path_id, ..}` value (as produced by
     /// [`qualify_pptrs`]), without going through `serde_json::Value`.
     pub fn from_val(v: &jaq_json::Val) -> Result<Self> {
@@ -96,7 +115,7 @@ pub fn qualify_pptrs<R: EnvResolver, P: TypeTreeProvider>(
 
 #[cfg(test)]
 mod tests {
-    use super::qualify_pptrs;
+    use super::{QualifiedPPtr, qualify_pptrs};
     use jaq_json::Val;
     use jaq_std::ValT as _;
     use rabex::objects::PPtr;
@@ -118,6 +137,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn is_qualified_requires_exactly_the_pptr_fields() {
+        assert!(QualifiedPPtr::is_qualified(&val(
+            r#"{ "file": "level0", "path_id": 1, "class_id": "GameObject" }"#
+        )));
+        assert!(!QualifiedPPtr::is_qualified(&val(
+            r#"{ "file": "level0", "path_id": 1 }"#
+        )));
+        assert!(!QualifiedPPtr::is_qualified(&val(
+            r#"{ "file": "level0", "path_id": 1, "class_id": "GameObject", "m_Name": "" }"#
+        )));
+        assert!(!QualifiedPPtr::is_qualified(&val("null")));
+    }
+
     #[test]
     fn qualifies_local_pptr_and_collapses_null_pptr() {
         // Flat writes a GameObject followed by its Transform; the Transform's `m_GameObject` points
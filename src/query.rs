@@ -2,14 +2,19 @@ use anyhow::{Context as _, Result, anyhow};
 use core::marker::PhantomData;
 use jaq_core::{Cv, DataT, Filter, Lut, Vars, ValXs, data, load, unwrap_valr};
 use jaq_json::Val;
+use jaq_std::ValT as _;
 use jaq_std::input::{self, Inputs};
 use rabex::objects::PPtr;
 use rabex::tpk::TpkTypeTreeBlob;
 use rabex::typetree::TypeTreeProvider;
 use rabex::typetree::typetree_cache::sync::TypeTreeCache;
 use rabex_env::Environment;
+use rabex_env::addressables::ArchivePath;
 use rabex_env::resolver::{EnvResolver, GameFiles};
+use std::path::PathBuf;
 
+use crate::asset_index::AssetIndex;
+use crate::inheritance::GameTypes;
 use crate::qualify_pptr::{QualifiedPPtr, qualify_pptrs};
 
 /// Capability trait giving a jaq run's context access to the [`Environment`], so the native
@@ -19,10 +24,16 @@ use crate::qualify_pptr::{QualifiedPPtr, qualify_pptrs};
 /// close over anything.
 pub trait HasEnv<'a, R, P> {
     fn env(&self) -> &'a Environment<R, P>;
+    /// Asset lookups of the env's game, shared across runs.
+    fn index(&self) -> &'a AssetIndex;
+    /// The types of the game's managed assemblies, shared across runs.
+    fn types(&self) -> &'a GameTypes;
 }
 
-fn deref<R: EnvResolver, P: TypeTreeProvider>(
+/// Load the object a qualified PPtr (`{file, path_id, ..}`) points to, enriched like scan results.
+pub fn deref<R: EnvResolver, P: TypeTreeProvider>(
     env: &Environment<R, P>,
+    index: &AssetIndex,
     pptr: jaq_json::Val,
 ) -> Result<jaq_json::Val> {
     let qualified_pptr = QualifiedPPtr::from_val(&pptr)?;
@@ -46,13 +57,64 @@ fn deref<R: EnvResolver, P: TypeTreeProvider>(
         &mut value,
         &qualified_pptr.file,
         &file,
+        qualified_pptr.path_id,
         script.as_ref(),
         None,
+        index,
     )?;
 
     Ok(value)
 }
 
+/// Load the main object of the addressable asset with the given address.
+pub fn addressable<R: EnvResolver, P: TypeTreeProvider>(
+    env: &Environment<R, P>,
+    index: &AssetIndex,
+    address: &str,
+) -> Result<jaq_json::Val> {
+    let asset = index
+        .find_address(address)
+        .with_context(|| format!("no addressable asset with address '{address}'"))?;
+    let aa = env
+        .addressables()?
+        .context("the game has no addressables")?;
+
+    for (cab, bundle) in &aa.cab_to_bundle {
+        if crate::catalog::file_name(&crate::format_path(bundle)) != asset.bundle {
+            continue;
+        }
+        let path = crate::format_path(&PathBuf::from(ArchivePath::same(cab)));
+        let file = env
+            .load_serialized(&path)
+            .with_context(|| format!("Failed to load '{path}'"))?;
+        if let Some(path_id) = index.container(&file, &path).main_object(&asset.asset_path) {
+            let pptr = QualifiedPPtr {
+                file: path,
+                path_id,
+            };
+            return deref(env, index, pptr.to_val());
+        }
+    }
+    Err(anyhow!(
+        "'{}' not found in bundle {}",
+        asset.asset_path,
+        asset.bundle
+    ))
+}
+
+/// The container path (see [`AssetIndex::asset_path`]) of the object a qualified PPtr points to.
+pub fn asset_path<R: EnvResolver, P: TypeTreeProvider>(
+    env: &Environment<R, P>,
+    index: &AssetIndex,
+    pptr: &jaq_json::Val,
+) -> Result<Option<String>> {
+    let qualified_pptr = QualifiedPPtr::from_val(pptr)?;
+    let file = env
+        .load_serialized(&qualified_pptr.file)
+        .with_context(|| format!("Failed to load '{}'", qualified_pptr.file))?;
+    Ok(index.asset_path(&file, &qualified_pptr.file, qualified_pptr.path_id))
+}
+
 // The native `deref` filter. Pulling the body into a generic fn with a *named* `'a` (rather than
 // inlining it in the closure) is what makes `ctx.data().env()` unambiguous — exactly how jaq-std's
 // `inputs` filter reaches its `HasInputs` data. The closure below is captureless, so it coerces to
@@ -64,25 +126,188 @@ where
 {
     let (ctx, val) = cv;
     // The env comes from the run's context (see `HasEnv`), not a global.
-    let env = ctx.data().env();
-    let obj = deref(env, val).map_err(|e| {
+    let data = ctx.data();
+    let obj = deref(data.env(), data.index(), val).map_err(|e| {
         jaq_core::Exn::from(jaq_core::Error::str(format!("Cannot call `deref`: {e}")))
     });
     Box::new(core::iter::once(obj))
 }
 
+/// The typetree schema (see [`crate::schema::typetree_schema`]) of an enriched MonoBehaviour,
+/// looked up through its `_asm` and `_type` fields.
+fn schema<R: EnvResolver, P: TypeTreeProvider>(
+    env: &Environment<R, P>,
+    object: &jaq_json::Val,
+) -> Result<jaq_json::Val> {
+    let field = |name: &str| -> Result<String> {
+        let jaq_json::Val::Obj(map) = object else {
+            return Err(anyhow!("expected an object, found {object}"));
+        };
+        let value = map
+            .iter()
+            .find(|(k, _)| k.as_utf8_bytes() == Some(name.as_bytes()))
+            .and_then(|(_, v)| v.as_utf8_bytes())
+            .with_context(|| format!("object has no `{name}`, is it a MonoBehaviour?"))?;
+        Ok(String::from_utf8_lossy(value).into_owned())
+    };
+    let tt = crate::schema::script_typetree(env, &field("_asm")?, &field("_type")?)?;
+    Ok(crate::schema::typetree_schema(&tt))
+}
+
+fn schema_native<'a, R, P>(cv: Cv<'a, DataKind<R, P>>) -> ValXs<'a, Val>
+where
+    R: EnvResolver + 'static,
+    P: TypeTreeProvider + 'static,
+{
+    let (ctx, val) = cv;
+    let env = ctx.data().env();
+    let schema = schema(env, &val).map_err(|e| {
+        jaq_core::Exn::from(jaq_core::Error::str(format!("Cannot call `schema`: {e}")))
+    });
+    Box::new(core::iter::once(schema))
+}
+
+fn asset_path_native<'a, R, P>(cv: Cv<'a, DataKind<R, P>>) -> ValXs<'a, Val>
+where
+    R: EnvResolver + 'static,
+    P: TypeTreeProvider + 'static,
+{
+    let (ctx, val) = cv;
+    let data = ctx.data();
+    let path = asset_path(data.env(), data.index(), &val)
+        .map(|path| path.map_or(Val::Null, Val::from))
+        .map_err(|e| {
+            jaq_core::Exn::from(jaq_core::Error::str(format!(
+                "Cannot call `asset_path`: {e}"
+            )))
+        });
+    Box::new(core::iter::once(path))
+}
+
+fn addressable_native<'a, R, P>(cv: Cv<'a, DataKind<R, P>>) -> ValXs<'a, Val>
+where
+    R: EnvResolver + 'static,
+    P: TypeTreeProvider + 'static,
+{
+    let (mut ctx, _) = cv;
+    let address = ctx.pop_var();
+    let data = ctx.data();
+    let obj = address
+        .as_utf8_bytes()
+        .context("the address must be a string")
+        .and_then(|address| {
+            addressable(data.env(), data.index(), &String::from_utf8_lossy(address))
+        })
+        .map_err(|e| {
+            jaq_core::Exn::from(jaq_core::Error::str(format!(
+                "Cannot call `addressable`: {e}"
+            )))
+        });
+    Box::new(core::iter::once(obj))
+}
+
+fn enum_name_native<'a, R, P>(cv: Cv<'a, DataKind<R, P>>) -> ValXs<'a, Val>
+where
+    R: EnvResolver + 'static,
+    P: TypeTreeProvider + 'static,
+{
+    let (mut ctx, val) = cv;
+    let ty = ctx.pop_var();
+    let types = ctx.data().types().get();
+    let name = ty
+        .as_utf8_bytes()
+        .context("the enum type must be a string")
+        .and_then(|ty| {
+            let value = val
+                .as_isize()
+                .context("the enum value must be an integer")?;
+            let name = types.enum_name(&String::from_utf8_lossy(ty), value as i64);
+            Ok(name.map_or(Val::Null, |name| Val::from(name.to_owned())))
+        })
+        .map_err(|e| {
+            jaq_core::Exn::from(jaq_core::Error::str(format!(
+                "Cannot call `enum_name`: {e}"
+            )))
+        });
+    Box::new(core::iter::once(name))
+}
+
+fn fsm_graph_native<'a, R, P>(cv: Cv<'a, DataKind<R, P>>) -> ValXs<'a, Val> {
+    let (_, val) = cv;
+    let graph = crate::playmaker::fsm_graph(&val).map_err(|e| {
+        jaq_core::Exn::from(jaq_core::Error::str(format!(
+            "Cannot call `fsm_graph`: {e}"
+        )))
+    });
+    Box::new(core::iter::once(graph))
+}
+
+fn fsm_dot_native<'a, R, P>(cv: Cv<'a, DataKind<R, P>>) -> ValXs<'a, Val> {
+    let (_, val) = cv;
+    let dot = Val::from(crate::playmaker::to_dot(&val));
+    Box::new(core::iter::once(Ok(dot)))
+}
+
 fn funs<R, P>() -> impl Iterator<Item = jaq_core::native::Fun<DataKind<R, P>>>
 where
     R: EnvResolver + 'static,
     P: TypeTreeProvider + 'static,
 {
-    [(
-        "deref",
-        vec![].into_boxed_slice(),
-        jaq_core::Native::new(|cv| deref_native::<R, P>(cv)),
-    )]
+    [
+        (
+            "deref",
+            vec![].into_boxed_slice(),
+            jaq_core::Native::new(|cv| deref_native::<R, P>(cv)),
+        ),
+        (
+            "schema",
+            vec![].into_boxed_slice(),
+            jaq_core::Native::new(|cv| schema_native::<R, P>(cv)),
+        ),
+        (
+            "asset_path",
+            vec![].into_boxed_slice(),
+            jaq_core::Native::new(|cv| asset_path_native::<R, P>(cv)),
+        ),
+        (
+            "addressable",
+            vec![jaq_core::Bind::Var(())].into_boxed_slice(),
+            jaq_core::Native::new(|cv| addressable_native::<R, P>(cv)),
+        ),
+        (
+            "enum_name",
+            vec![jaq_core::Bind::Var(())].into_boxed_slice(),
+            jaq_core::Native::new(|cv| enum_name_native::<R, P>(cv)),
+        ),
+        (
+            "fsm_graph",
+            vec![].into_boxed_slice(),
+            jaq_core::Native::new(|cv| fsm_graph_native::<R, P>(cv)),
+        ),
+        (
+            "fsm_dot",
+            vec![].into_boxed_slice(),
+            jaq_core::Native::new(|cv| fsm_dot_native::<R, P>(cv)),
+        ),
+    ]
     .into_iter()
 }
+/// Names of the uniscan-specific filters: the definitions from `defs.jq` and the natives.
+/// Sorted and deduplicated, arities are not distinguished.
+pub fn builtin_names() -> Vec<&'static str> {
+    let defs = include_str!("defs.jq").lines().filter_map(|line| {
+        let name = line.trim_start().strip_prefix("def ")?;
+        let end = name.find(['(', ':', ' ']).unwrap_or(name.len());
+        Some(&name[..end])
+    });
+    let natives = funs::<GameFiles, TypeTreeCache<TpkTypeTreeBlob>>().map(|(name, _, _)| name);
+
+    let mut names: Vec<_> = defs.chain(natives).collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
 pub struct QueryRunner<R = GameFiles, P = TypeTreeCache<TpkTypeTreeBlob>>
 where
     R: 'static,
@@ -170,12 +395,20 @@ impl<R: EnvResolver + 'static, P: TypeTreeProvider + 'static> QueryRunner<R, P>
         Ok(QueryRunner { filter })
     }
 
-    pub fn exec(&self, env: &Environment<R, P>, item: jaq_json::Val) -> Result<Vec<jaq_json::Val>> {
+    pub fn exec(
+        &self,
+        env: &Environment<R, P>,
+        index: &AssetIndex,
+        types: &GameTypes,
+        item: jaq_json::Val,
+    ) -> Result<Vec<jaq_json::Val>> {
         let inputs = jaq_std::input::RcIter::new(core::iter::empty());
         let data = Data {
             lut: &self.filter.lut,
             inputs: &inputs,
             env,
+            index,
+            types,
         };
         let out = self.filter.id.run::<DataKind<R, P>>((
             jaq_core::Ctx::new(&data, Vars::new(core::iter::empty())),
@@ -190,6 +423,8 @@ impl<R: EnvResolver + 'static, P: TypeTreeProvider + 'static> QueryRunner<R, P>
 #[cfg(test)]
 mod tests {
     use super::QueryRunner;
+    use crate::asset_index::AssetIndex;
+    use crate::inheritance::GameTypes;
     use jaq_json::Val;
 
     /// Parse a single JSON value into a `Val` using jaq's own reader.
@@ -210,7 +445,14 @@ mod tests {
         let env = Environment::new(MemResolver::new(), tpk);
 
         let runner = QueryRunner::new(query).unwrap();
-        runner.exec(&env, val(input)).unwrap()
+        runner
+            .exec(
+                &env,
+                &AssetIndex::default(),
+                &GameTypes::default(),
+                val(input),
+            )
+            .unwrap()
     }
 
     #[test]
@@ -229,6 +471,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unity_event_lists_calls_with_their_argument() {
+        let event = r#"{"m_PersistentCalls": {"m_Calls": [
+            {"m_Target": null, "m_MethodName": "SetHealth", "m_Mode": 3,
+             "m_Arguments": {"m_IntArgument": 5, "m_StringArgument": ""}},
+            {"m_Target": null, "m_MethodName": "Play", "m_Mode": 1,
+             "m_Arguments": {"m_IntArgument": 0, "m_StringArgument": ""}}
+        ]}}"#;
+        assert_eq!(
+            run("unity_event", event),
+            vec![val(r#"[
+                {"target_path": null, "target_script": null, "method": "SetHealth", "argument": 5},
+                {"target_path": null, "target_script": null, "method": "Play", "argument": null}
+            ]"#)],
+        );
+        assert_eq!(
+            run(
+                "with_unity_events | .button._calls[0].method",
+                &format!(r#"{{"button": {event}}}"#)
+            ),
+            vec![val(r#""SetHealth""#)],
+        );
+    }
+
+    /// A ScriptableObject target is a MonoBehaviour with a null `m_GameObject`, so it has no path.
+    #[test]
+    fn unity_event_target_path_is_null_for_a_scriptable_object() {
+        let target = r#"{"m_GameObject": null, "m_Name": "Settings", "_type": "GameSettings"}"#;
+        assert_eq!(run("unity_event_target_path", target), vec![val("null")]);
+    }
+
     #[test]
     fn maybe_guards_null() {
         assert_eq!(run("maybe(. + 1)", "null"), vec![val("null")]);
@@ -264,6 +537,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn builtin_names_cover_defs_and_natives() {
+        let names = super::builtin_names();
+        for name in [
+            "maybe",
+            "go",
+            "components",
+            "depth3",
+            "deref",
+            "schema",
+            "asset_path",
+            "addressable",
+            "enum_name",
+            "fsm_graph",
+            "fsm_dot",
+        ] {
+            assert!(names.contains(&name), "missing {name}");
+        }
+        // `components` and `components(class_id)` are listed once
+        assert_eq!(
+            names.iter().filter(|&&name| name == "components").count(),
+            1
+        );
+    }
+
     #[test]
     fn invalid_query_is_a_compile_error() {
         let result: anyhow::Result<QueryRunner> = QueryRunner::new(".[");
@@ -293,9 +591,50 @@ mod tests {
 
         let runner = QueryRunner::new("deref | .m_Name").unwrap();
         let pptr = val(&format!(r#"{{ "file": "level0", "path_id": {} }}"#, go_ids[0]));
-        let out = runner.exec(&env, pptr).unwrap();
+        let out = runner
+            .exec(&env, &AssetIndex::default(), &GameTypes::default(), pptr)
+            .unwrap();
         assert_eq!(out, vec![val(r#""Player""#)]);
     }
+
+    /// Staged like [`deref_reads_through_a_qualified_pptr`], with a Transform as the target.
+    #[test]
+    fn unity_event_resolves_its_target() {
+        use rabex_env::Environment;
+        use rabex_env::resolver::GameFiles;
+        use rabex_env_testkit::Flat;
+
+        let (bytes, go_ids) = Flat::new(&["Door"]).write();
+        let tmp = tempfile::TempDir::new().unwrap();
+        let data_dir = tmp.path().join("Game_Data");
+        std::fs::create_dir(&data_dir).unwrap();
+        std::fs::write(data_dir.join("level0"), bytes).unwrap();
+
+        let game_files = GameFiles::probe(tmp.path()).unwrap();
+        let tpk = rabex::typetree::typetree_cache::sync::TypeTreeCache::new(
+            rabex::tpk::TpkTypeTreeBlob::embedded(),
+        );
+        let env = Environment::new(game_files, tpk);
+
+        let transform_id = go_ids[0] + 1;
+        let event = val(&format!(
+            r#"{{"m_PersistentCalls": {{"m_Calls": [
+                {{"m_Target": {{"file": "level0", "path_id": {transform_id}, "class_id": "Transform"}},
+                 "m_MethodName": "SetActive", "m_Mode": 6,
+                 "m_Arguments": {{"m_BoolArgument": true}}}}
+            ]}}}}"#
+        ));
+        let runner = QueryRunner::new("unity_event").unwrap();
+        let out = runner
+            .exec(&env, &AssetIndex::default(), &GameTypes::default(), event)
+            .unwrap();
+        assert_eq!(
+            out,
+            vec![val(
+                r#"[{"target_path": "Door", "target_script": "Transform", "method": "SetActive", "argument": true}]"#
+            )]
+        );
+    }
 }
 
 // `DataT` must be `'static`, so the resolver/provider ride along as `PhantomData` type params
@@ -316,6 +655,8 @@ where
     lut: &'a Lut<DataKind<R, P>>,
     inputs: Inputs<'a, Val>,
     env: &'a Environment<R, P>,
+    index: &'a AssetIndex,
+    types: &'a GameTypes,
 }
 
 impl<'a, R: 'static, P: 'static> Data<'a, R, P> {
@@ -323,8 +664,16 @@ impl<'a, R: 'static, P: 'static> Data<'a, R, P> {
         lut: &'a Lut<DataKind<R, P>>,
         inputs: Inputs<'a, Val>,
         env: &'a Environment<R, P>,
+        index: &'a AssetIndex,
+        types: &'a GameTypes,
     ) -> Self {
-        Self { lut, inputs, env }
+        Self {
+            lut,
+            inputs,
+            env,
+            index,
+            types,
+        }
     }
 }
 
@@ -338,6 +687,12 @@ impl<'a, R: 'static, P: 'static> HasEnv<'a, R, P> for &'a Data<'a, R, P> {
     fn env(&self) -> &'a Environment<R, P> {
         self.env
     }
+    fn index(&self) -> &'a AssetIndex {
+        self.index
+    }
+    fn types(&self) -> &'a GameTypes {
+        self.types
+    }
 }
 
 impl<'a, R: 'static, P: 'static> input::HasInputs<'a, Val> for &'a Data<'a, R, P> {
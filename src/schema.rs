@@ -0,0 +1,270 @@
+use anyhow::{Context as _, Result};
+use jaq_json::Val;
+use jaq_std::ValT as _;
+use rabex::typetree::{TypeTreeNode, TypeTreeProvider};
+use rabex_env::Environment;
+use rabex_env::resolver::EnvResolver;
+
+use crate::hierarchy::field;
+
+/// Resolve the MonoBehaviour typetree of the script `full_name` in `assembly`, generated from the
+/// game's managed assemblies. No instance of the script needs to exist.
+pub fn script_typetree<R: EnvResolver, P: TypeTreeProvider>(
+    env: &Environment<R, P>,
+    assembly: &str,
+    full_name: &str,
+) -> Result<TypeTreeNode> {
+    env.typetree_generator
+        .generate(assembly, full_name)
+        .with_context(|| format!("Could not generate typetree for '{full_name}' in {assembly}"))
+}
+
+/// Describe a typetree node as a value:
+/// - `{name, type, kind: "primitive"}` for leaves,
+/// - `{name, type, kind: "string"}`,
+/// - `{name, type, kind: "pptr", target}` where `target` is the referenced class,
+/// - `{name, type, kind: "array", element}` for `vector`s, C# arrays and lists,
+/// - `{name, type, kind: "struct", fields: [..]}` for everything else.
+pub fn typetree_schema(node: &TypeTreeNode) -> Val {
+    let mut obj = jaq_json::Map::default();
+    obj.insert("name".to_string().into(), node.m_Name.clone().into());
+    obj.insert("type".to_string().into(), node.m_Type.clone().into());
+
+    let array = node.children.iter().find(|child| child.m_Type == "Array");
+    let kind = if node.m_Type == "string" {
+        "string"
+    } else if let Some(target) = pptr_target(&node.m_Type) {
+        obj.insert("target".to_string().into(), target.to_owned().into());
+        "pptr"
+    } else if let Some(array) = array.or((node.m_Type == "Array").then_some(node)) {
+        // `Array` holds `size` followed by the element node `data`
+        if let Some(element) = array.children.get(1) {
+            obj.insert("element".to_string().into(), typetree_schema(element));
+        }
+        "array"
+    } else if node.children.is_empty() {
+        "primitive"
+    } else {
+        let fields: Val = node.children.iter().map(typetree_schema).collect();
+        obj.insert("fields".to_string().into(), fields);
+        "struct"
+    };
+    obj.insert("kind".to_string().into(), kind.to_string().into());
+
+    Val::obj(obj)
+}
+
+/// All field names occurring anywhere in a schema produced by [`typetree_schema`], sorted.
+pub fn field_names(schema: &Val) -> Vec<String> {
+    fn collect(schema: &Val, names: &mut Vec<String>) {
+        if let Some(Val::Arr(fields)) = field(schema, "fields") {
+            for field_schema in fields.iter() {
+                if let Some(name) = field(field_schema, "name").and_then(|v| v.as_utf8_bytes()) {
+                    names.push(String::from_utf8_lossy(name).into_owned());
+                }
+                collect(field_schema, names);
+            }
+        }
+        if let Some(element) = field(schema, "element") {
+            collect(element, names);
+        }
+    }
+
+    let mut names = Vec::new();
+    collect(schema, &mut names);
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// `PPtr<$GameObject>` -> `GameObject`
+fn pptr_target(ty: &str) -> Option<&str> {
+    let inner = ty.strip_prefix("PPtr<")?.strip_suffix('>')?;
+    Some(inner.trim_start_matches('$'))
+}
+
+/// Convert the output of [`typetree_schema`] into a JSON Schema (draft 2020-12) describing the
+/// objects uniscan emits, i.e. with PPtrs already qualified to `{file, path_id, class_id}`.
+pub fn json_schema(schema: &Val) -> Val {
+    let mut root = json_schema_inner(schema);
+    if let Val::Obj(map) = &mut root {
+        let map = jaq_json::Rc::make_mut(map);
+        map.insert(
+            "$schema".to_string().into(),
+            "https://json-schema.org/draft/2020-12/schema"
+                .to_string()
+                .into(),
+        );
+        if let Some(ty) = field(schema, "type").and_then(|v| v.as_utf8_bytes()) {
+            let title = String::from_utf8_lossy(ty).into_owned();
+            map.insert("title".to_string().into(), title.into());
+        }
+    }
+    root
+}
+
+fn json_schema_inner(schema: &Val) -> Val {
+    let str_field = |name: &str| {
+        field(schema, name)
+            .and_then(|v| v.as_utf8_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+            .unwrap_or_default()
+    };
+    let ty = str_field("type");
+    let kind = str_field("kind");
+
+    let mut obj = jaq_json::Map::default();
+    match kind.as_str() {
+        "string" => {
+            obj.insert("type".to_string().into(), "string".to_string().into());
+        }
+        "primitive" => {
+            let json_type = match ty.as_str() {
+                "bool" => "boolean",
+                "float" | "double" => "number",
+                _ => "integer",
+            };
+            obj.insert("type".to_string().into(), json_type.to_string().into());
+        }
+        "pptr" => {
+            let mut properties = jaq_json::Map::default();
+            for name in ["file", "class_id"] {
+                properties.insert(name.to_string().into(), type_only("string"));
+            }
+            properties.insert("path_id".to_string().into(), type_only("integer"));
+            let types: Val = ["object", "null"]
+                .into_iter()
+                .map(|t| Val::from(t.to_string()))
+                .collect();
+            obj.insert("type".to_string().into(), types);
+            obj.insert("properties".to_string().into(), Val::obj(properties));
+            obj.insert(
+                "description".to_string().into(),
+                format!("PPtr to {}", str_field("target")).into(),
+            );
+        }
+        "array" => {
+            obj.insert("type".to_string().into(), "array".to_string().into());
+            if let Some(element) = field(schema, "element") {
+                obj.insert("items".to_string().into(), json_schema_inner(element));
+            }
+        }
+        _ => {
+            let mut properties = jaq_json::Map::default();
+            if let Some(Val::Arr(fields)) = field(schema, "fields") {
+                for field_schema in fields.iter() {
+                    let Some(name) = field(field_schema, "name").and_then(|v| v.as_utf8_bytes())
+                    else {
+                        continue;
+                    };
+                    let name = String::from_utf8_lossy(name).into_owned();
+                    properties.insert(name.into(), json_schema_inner(field_schema));
+                }
+            }
+            obj.insert("type".to_string().into(), "object".to_string().into());
+            obj.insert("properties".to_string().into(), Val::obj(properties));
+        }
+    }
+    if kind != "pptr" && !ty.is_empty() {
+        obj.insert("description".to_string().into(), ty.into());
+    }
+
+    Val::obj(obj)
+}
+
+fn type_only(ty: &str) -> Val {
+    let mut obj = jaq_json::Map::default();
+    obj.insert("type".to_string().into(), ty.to_string().into());
+    Val::obj(obj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{field_names, json_schema, pptr_target};
+    use jaq_json::Val;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn pptr_target_strips_the_managed_marker() {
+        assert_eq!(pptr_target("PPtr<GameObject>"), Some("GameObject"));
+        assert_eq!(
+            pptr_target("PPtr<$EnemyDeathEffects>"),
+            Some("EnemyDeathEffects")
+        );
+        assert_eq!(pptr_target("vector"), None);
+    }
+
+    #[test]
+    fn field_names_include_nested_structs_and_array_elements() {
+        let schema = val(r#"{
+                "name": "Base", "type": "Enemy", "kind": "struct", "fields": [
+                    { "name": "hp", "type": "int", "kind": "primitive" },
+                    { "name": "stats", "type": "Stats", "kind": "struct", "fields": [
+                        { "name": "speed", "type": "float", "kind": "primitive" }
+                    ] },
+                    { "name": "drops", "type": "vector", "kind": "array", "element":
+                        { "name": "data", "type": "Drop", "kind": "struct", "fields": [
+                            { "name": "hp", "type": "int", "kind": "primitive" }
+                        ] }
+                    }
+                ]
+            }"#);
+        assert_eq!(field_names(&schema), ["drops", "hp", "speed", "stats"]);
+    }
+
+    #[test]
+    fn json_schema_maps_kinds_to_json_types() {
+        let schema = val(r#"{
+                "name": "Base", "type": "HealthManager", "kind": "struct", "fields": [
+                    { "name": "hp", "type": "int", "kind": "primitive" },
+                    { "name": "speed", "type": "float", "kind": "primitive" },
+                    { "name": "m_Name", "type": "string", "kind": "string" },
+                    { "name": "drops", "type": "vector", "kind": "array",
+                      "element": { "name": "data", "type": "bool", "kind": "primitive" } }
+                ]
+            }"#);
+        assert_eq!(
+            json_schema(&schema),
+            val(r#"{
+                "type": "object",
+                "properties": {
+                    "hp": { "type": "integer", "description": "int" },
+                    "speed": { "type": "number", "description": "float" },
+                    "m_Name": { "type": "string", "description": "string" },
+                    "drops": {
+                        "type": "array",
+                        "items": { "type": "boolean", "description": "bool" },
+                        "description": "vector"
+                    }
+                },
+                "description": "HealthManager",
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "HealthManager"
+            }"#),
+        );
+    }
+
+    #[test]
+    fn json_schema_describes_qualified_pptrs() {
+        let schema = val(
+            r#"{ "name": "go", "type": "PPtr<GameObject>", "kind": "pptr", "target": "GameObject" }"#,
+        );
+        assert_eq!(
+            json_schema(&schema),
+            val(r#"{
+                "type": ["object", "null"],
+                "properties": {
+                    "file": { "type": "string" },
+                    "class_id": { "type": "string" },
+                    "path_id": { "type": "integer" }
+                },
+                "description": "PPtr to GameObject",
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "PPtr<GameObject>"
+            }"#),
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use jaq_json::Val;
+use jaq_std::ValT as _;
+use regex::Regex;
+
+/// A pattern to find among already computed results.
+pub enum Pattern {
+    /// Case-insensitive substring, stored lowercase.
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, regex: bool) -> Result<Pattern> {
+        Ok(match regex {
+            true => Pattern::Regex(Regex::new(pattern)?),
+            false => Pattern::Substring(pattern.to_lowercase()),
+        })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Pattern::Substring(needle) => text.to_lowercase().contains(needle),
+            Pattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+
+    /// Whether any key or scalar value in `value` matches. Numbers, booleans and null are
+    /// matched in their JSON form, strings without quotes.
+    pub fn matches_value(&self, value: &Val) -> bool {
+        match value {
+            Val::Arr(values) => values.iter().any(|v| self.matches_value(v)),
+            Val::Obj(map) => map.iter().any(|(k, v)| {
+                k.as_utf8_bytes()
+                    .is_some_and(|k| self.is_match(&String::from_utf8_lossy(k)))
+                    || self.matches_value(v)
+            }),
+            scalar => match scalar.as_utf8_bytes() {
+                Some(text) => self.is_match(&String::from_utf8_lossy(text)),
+                None => self.is_match(&crate::to_pretty_json(scalar)),
+            },
+        }
+    }
+}
+
+/// Indices of the items containing a match.
+pub fn find(items: &[Val], pattern: &Pattern) -> Vec<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| pattern.matches_value(item))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Indices of the table rows with a matching cell.
+pub fn find_rows(rows: &[Vec<Val>], pattern: &Pattern) -> Vec<usize> {
+    rows.iter()
+        .enumerate()
+        .filter(|(_, row)| row.iter().any(|cell| pattern.matches_value(cell)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pattern, find, find_rows};
+    use jaq_json::Val;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn substring_is_case_insensitive_over_keys_and_values() {
+        let pattern = Pattern::new("hornet", false).unwrap();
+        assert!(pattern.matches_value(&val(r#"{"m_Name": "Hornet Boss"}"#)));
+        assert!(pattern.matches_value(&val(r#"{"nested": [{"HORNET": 1}]}"#)));
+        assert!(!pattern.matches_value(&val(r#"{"m_Name": "Moss Knight"}"#)));
+    }
+
+    #[test]
+    fn scalars_match_in_their_json_form() {
+        assert!(
+            Pattern::new("12", false)
+                .unwrap()
+                .matches_value(&val(r#"{"hp": 120}"#))
+        );
+        assert!(
+            Pattern::new("true", false)
+                .unwrap()
+                .matches_value(&val(r#"[true]"#))
+        );
+    }
+
+    #[test]
+    fn regex_patterns() {
+        let pattern = Pattern::new("^level[0-9]+$", true).unwrap();
+        assert!(pattern.matches_value(&val(r#"{"_file": "level12"}"#)));
+        assert!(!pattern.matches_value(&val(r#"{"_file": "sharedassets12"}"#)));
+        assert!(Pattern::new("(", true).is_err());
+    }
+
+    #[test]
+    fn find_returns_matching_indices() {
+        let items = [
+            val(r#"{"a": "x"}"#),
+            val(r#"{"a": "y"}"#),
+            val(r#"{"x": 1}"#),
+        ];
+        assert_eq!(find(&items, &Pattern::new("x", false).unwrap()), [0, 2]);
+    }
+
+    #[test]
+    fn find_rows_matches_cells() {
+        let rows = [
+            vec![val(r#""Hornet""#), val("3")],
+            vec![val(r#""Moss Knight""#), val("12")],
+        ];
+        assert_eq!(
+            find_rows(&rows, &Pattern::new("hornet", false).unwrap()),
+            [0]
+        );
+        assert_eq!(find_rows(&rows, &Pattern::new("12", false).unwrap()), [1]);
+    }
+}
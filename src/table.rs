@@ -0,0 +1,130 @@
+use jaq_json::Val;
+use jaq_std::ValT as _;
+
+/// Results laid out as rows and columns, for when every result is a flat object.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Val>>,
+}
+
+impl Table {
+    /// Build a table if `items` are homogeneous: all objects with the same keys, whose values are
+    /// scalars. Columns are in the key order of the first item.
+    pub fn from_items(items: &[Val]) -> Option<Table> {
+        let first = items.first()?;
+        let Val::Obj(first) = first else {
+            return None;
+        };
+        let columns: Vec<String> = first
+            .keys()
+            .map(|key| {
+                key.as_utf8_bytes()
+                    .map(|k| String::from_utf8_lossy(k).into_owned())
+            })
+            .collect::<Option<_>>()?;
+
+        let mut rows = Vec::with_capacity(items.len());
+        for item in items {
+            let Val::Obj(obj) = item else {
+                return None;
+            };
+            if obj.len() != columns.len() {
+                return None;
+            }
+            let row = columns
+                .iter()
+                .map(|column| {
+                    let (_, value) = obj
+                        .iter()
+                        .find(|(k, _)| k.as_utf8_bytes() == Some(column.as_bytes()))?;
+                    (!matches!(value, Val::Arr(_) | Val::Obj(_))).then(|| value.clone())
+                })
+                .collect::<Option<Vec<_>>>()?;
+            rows.push(row);
+        }
+
+        Some(Table { columns, rows })
+    }
+
+    /// Stable sort by a column, in jq's value order.
+    pub fn sort_by(&mut self, column: usize, descending: bool) {
+        self.rows.sort_by(|a, b| {
+            let ordering = a[column].cmp(&b[column]);
+            match descending {
+                true => ordering.reverse(),
+                false => ordering,
+            }
+        });
+    }
+}
+
+/// Text of a table cell: strings without quotes, everything else as JSON.
+pub fn cell_text(value: &Val) -> String {
+    match value.as_utf8_bytes() {
+        Some(text) => String::from_utf8_lossy(text).into_owned(),
+        None => crate::to_pretty_json(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Table, cell_text};
+    use jaq_json::Val;
+
+    fn val(s: &str) -> Val {
+        jaq_json::read::parse_single(s.as_bytes()).unwrap()
+    }
+
+    fn items(s: &str) -> Vec<Val> {
+        match val(s) {
+            Val::Arr(items) => items.iter().cloned().collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn flat_homogeneous_objects_become_a_table() {
+        let table = Table::from_items(&items(
+            r#"[{"name": "a", "hp": 5}, {"hp": 3, "name": "b"}]"#,
+        ))
+        .unwrap();
+        assert_eq!(table.columns, ["name", "hp"]);
+        assert_eq!(
+            table.rows,
+            [[val(r#""a""#), val("5")], [val(r#""b""#), val("3")]],
+        );
+    }
+
+    #[test]
+    fn differing_keys_or_nested_values_are_not_a_table() {
+        assert_eq!(
+            Table::from_items(&items(r#"[{"name": "a"}, {"hp": 3}]"#)),
+            None
+        );
+        assert_eq!(
+            Table::from_items(&items(r#"[{"name": "a"}, {"name": "b", "hp": 3}]"#)),
+            None
+        );
+        assert_eq!(Table::from_items(&items(r#"[{"pos": [1, 2]}]"#)), None);
+        assert_eq!(Table::from_items(&items(r#"[1, 2]"#)), None);
+        assert_eq!(Table::from_items(&[]), None);
+    }
+
+    #[test]
+    fn sort_by_column_in_both_directions() {
+        let mut table =
+            Table::from_items(&items(r#"[{"hp": 5}, {"hp": null}, {"hp": 12}]"#)).unwrap();
+        table.sort_by(0, false);
+        assert_eq!(table.rows, [[val("null")], [val("5")], [val("12")]]);
+        table.sort_by(0, true);
+        assert_eq!(table.rows, [[val("12")], [val("5")], [val("null")]]);
+    }
+
+    #[test]
+    fn cell_text_unquotes_strings() {
+        assert_eq!(cell_text(&val(r#""Hornet""#)), "Hornet");
+        assert_eq!(cell_text(&val("1.5")), "1.5");
+        assert_eq!(cell_text(&val("null")), "null");
+    }
+}
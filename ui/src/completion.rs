@@ -0,0 +1,69 @@
+const MAX_COMPLETIONS: usize = 8;
+
+/// The identifier at the end of the query that is currently being typed.
+pub struct Token<'a> {
+    pub prefix: &'a str,
+    /// Whether the identifier follows a `.`, i.e. is a field access rather than a filter call.
+    pub field: bool,
+}
+
+pub fn current_token(query: &str) -> Token<'_> {
+    let start = query
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    Token {
+        prefix: &query[start..],
+        field: query[..start].ends_with('.'),
+    }
+}
+
+/// Candidates for the token being typed: field names after a `.`, builtin filters otherwise.
+pub fn completions<'a>(query: &str, fields: &'a [String], builtins: &'a [&str]) -> Vec<&'a str> {
+    let token = current_token(query);
+    let candidates: Box<dyn Iterator<Item = &str>> = match token.field {
+        true => Box::new(fields.iter().map(String::as_str)),
+        false if token.prefix.is_empty() => return Vec::new(),
+        false => Box::new(builtins.iter().copied()),
+    };
+
+    let prefix = token.prefix.to_ascii_lowercase();
+    candidates
+        .filter(|candidate| {
+            *candidate != token.prefix && candidate.to_ascii_lowercase().starts_with(&prefix)
+        })
+        .take(MAX_COMPLETIONS)
+        .collect()
+}
+
+/// Replace the token being typed with `completion`.
+pub fn apply(query: &str, completion: &str) -> String {
+    let token = current_token(query);
+    let mut query = query[..query.len() - token.prefix.len()].to_owned();
+    query.push_str(completion);
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::current_token;
+
+    #[test]
+    fn token_after_a_field_access() {
+        let token = current_token(".m_Component[].comp");
+        assert_eq!(token.prefix, "comp");
+        assert!(token.field);
+    }
+
+    #[test]
+    fn token_after_a_multibyte_separator() {
+        let token = current_token(".m_Name == \"€");
+        assert_eq!(token.prefix, "");
+        assert!(!token.field);
+
+        let token = current_token("select(.m_Name == “abc");
+        assert_eq!(token.prefix, "abc");
+        assert!(!token.field);
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// How many history entries are kept per game.
+const MAX_HISTORY: usize = 50;
+
+fn config_dir() -> Result<PathBuf> {
+    let dir = dirs::config_dir().context("no config directory on this platform")?;
+    Ok(dir.join("uniscan"))
+}
+
+/// Read `<config dir>/uniscan/<name>`, falling back to the default if it is missing or invalid.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let read = || -> Result<Option<T>> {
+        let path = config_dir()?.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read(&path)?;
+        let value = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+        Ok(Some(value))
+    };
+    match read() {
+        Ok(value) => value.unwrap_or_default(),
+        Err(e) => {
+            warn!("{e:?}");
+            T::default()
+        }
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    let dir = config_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(name), serde_json::to_vec_pretty(value)?)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    pub script_filter: String,
+    pub query: String,
+    pub limit: usize,
+}
+
+/// Query history and saved queries, keyed by game name.
+#[derive(Default, Serialize, Deserialize)]
+pub struct QueryStore {
+    /// Most recent first.
+    #[serde(default)]
+    pub history: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub saved: HashMap<String, Vec<SavedQuery>>,
+}
+
+impl QueryStore {
+    pub const FILE: &str = "queries.json";
+
+    pub fn load() -> Self {
+        load(Self::FILE)
+    }
+    pub fn save(&self) -> Result<()> {
+        save(Self::FILE, self)
+    }
+
+    pub fn history(&self, game: &str) -> &[String] {
+        self.history.get(game).map_or(&[], Vec::as_slice)
+    }
+    pub fn saved(&self, game: &str) -> &[SavedQuery] {
+        self.saved.get(game).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn push_history(&mut self, game: &str, query: &str) {
+        let history = self.history.entry(game.to_owned()).or_default();
+        history.retain(|entry| entry != query);
+        history.insert(0, query.to_owned());
+        history.truncate(MAX_HISTORY);
+    }
+
+    /// Add saved queries, replacing existing ones with the same name.
+    pub fn save_queries(&mut self, game: &str, queries: impl IntoIterator<Item = SavedQuery>) {
+        let saved = self.saved.entry(game.to_owned()).or_default();
+        for query in queries {
+            match saved.iter_mut().find(|saved| saved.name == query.name) {
+                Some(existing) => *existing = query,
+                None => saved.push(query),
+            }
+        }
+    }
+
+    pub fn remove_saved(&mut self, game: &str, name: &str) {
+        if let Some(saved) = self.saved.get_mut(game) {
+            saved.retain(|saved| saved.name != name);
+        }
+    }
+}
+
+/// How many recently opened games are remembered.
+const MAX_RECENT_GAMES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnityGame {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// What was last used for a game, restored when it is opened again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub query: String,
+    pub script_filter: String,
+    pub limit: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Most recent first, including games opened from a custom path.
+    pub recent_games: Vec<UnityGame>,
+    /// Keyed by game name.
+    pub sessions: HashMap<String, Session>,
+    /// Logical window size when the app was last closed, restored on start.
+    pub window_size: Option<(f64, f64)>,
+    /// Folders whose subdirectories are probed for games, next to the launchers.
+    pub library_folders: Vec<PathBuf>,
+    /// Open the most recent game on start instead of showing the game selection.
+    pub reopen_last_game: bool,
+}
+
+impl Settings {
+    pub const FILE: &str = "settings.json";
+
+    pub fn load() -> Self {
+        load(Self::FILE)
+    }
+    pub fn save(&self) -> Result<()> {
+        save(Self::FILE, self)
+    }
+
+    pub fn push_recent(&mut self, game: &UnityGame) {
+        self.recent_games.retain(|recent| recent.path != game.path);
+        self.recent_games.insert(0, game.clone());
+        self.recent_games.truncate(MAX_RECENT_GAMES);
+    }
+    pub fn remove_recent(&mut self, index: usize) {
+        if index < self.recent_games.len() {
+            self.recent_games.remove(index);
+        }
+    }
+
+    pub fn session(&self, game: &str) -> Option<&Session> {
+        self.sessions.get(game)
+    }
+}
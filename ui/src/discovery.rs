@@ -0,0 +1,330 @@
+//! Finding installed unity games across launchers.
+//!
+//! Every [`Source`] lists candidate install directories, which are then probed with
+//! [`GameFiles::probe_dir`]. A launcher that isn't installed simply has no candidates.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use rabex_env::resolver::GameFiles;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::config::UnityGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    Steam,
+    /// Epic games installed through Heroic or the legendary CLI.
+    Epic,
+    /// GOG games installed through Heroic.
+    Gog,
+    Itch,
+    Lutris,
+    /// A user-configured library folder.
+    Library,
+}
+
+impl Launcher {
+    pub fn label(self) -> &'static str {
+        match self {
+            Launcher::Steam => "Steam",
+            Launcher::Epic => "Epic",
+            Launcher::Gog => "GOG",
+            Launcher::Itch => "itch.io",
+            Launcher::Lutris => "Lutris",
+            Launcher::Library => "Library",
+        }
+    }
+}
+
+pub struct DiscoveredGame {
+    pub game: UnityGame,
+    pub launcher: Launcher,
+}
+
+/// A directory that may contain a game, with the name the launcher knows it by.
+struct Candidate {
+    name: String,
+    dir: PathBuf,
+}
+
+trait Source {
+    fn launcher(&self) -> Launcher;
+    fn candidates(&self) -> Result<Vec<Candidate>>;
+}
+
+/// Find games of every launcher, and in the subdirectories of `library_folders`.
+///
+/// A launcher whose data can't be read is logged and skipped, as are single malformed entries of
+/// a launcher. Games reachable from several sources are listed once, for the first source.
+pub fn find_games(library_folders: &[PathBuf]) -> Vec<DiscoveredGame> {
+    let sources: [Box<dyn Source>; 6] = [
+        Box::new(Steam),
+        Box::new(Epic),
+        Box::new(Gog),
+        Box::new(Itch),
+        Box::new(Lutris),
+        Box::new(Library(library_folders.to_vec())),
+    ];
+
+    let mut seen = HashSet::new();
+    let mut games = Vec::new();
+    for source in sources {
+        let candidates = match source.candidates() {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                warn!(
+                    "Could not discover {} games: {e:?}",
+                    source.launcher().label()
+                );
+                continue;
+            }
+        };
+        for candidate in candidates {
+            let Ok(path) = GameFiles::probe_dir(&candidate.dir) else {
+                continue;
+            };
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            games.push(DiscoveredGame {
+                game: UnityGame {
+                    name: candidate.name,
+                    path,
+                },
+                launcher: source.launcher(),
+            });
+        }
+    }
+    games
+}
+
+struct Steam;
+impl Source for Steam {
+    fn launcher(&self) -> Launcher {
+        Launcher::Steam
+    }
+    fn candidates(&self) -> Result<Vec<Candidate>> {
+        let Ok(steam_dir) = steamlocate::SteamDir::locate() else {
+            return Ok(Vec::new());
+        };
+
+        let mut candidates = Vec::new();
+        for lib in steam_dir.libraries()? {
+            let Some(lib) = skip_invalid(lib, "Steam library") else {
+                continue;
+            };
+            for app in lib.apps() {
+                let Some(app) = skip_invalid(app, "Steam app manifest") else {
+                    continue;
+                };
+                candidates.push(Candidate {
+                    name: app.name.clone().unwrap_or_else(|| app.install_dir.clone()),
+                    dir: lib.resolve_app_dir(&app),
+                });
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+/// legendary's `installed.json`, also used by Heroic: `{ "<app>": { "title", "install_path" } }`.
+struct Epic;
+impl Source for Epic {
+    fn launcher(&self) -> Launcher {
+        Launcher::Epic
+    }
+    fn candidates(&self) -> Result<Vec<Candidate>> {
+        let Some(config) = dirs::config_dir() else {
+            return Ok(Vec::new());
+        };
+        let files = [
+            config.join("heroic/legendaryConfig/legendary/installed.json"),
+            config.join("legendary/installed.json"),
+        ];
+
+        let mut candidates = Vec::new();
+        for file in files {
+            let Some(Some(Value::Object(installed))) = skip_invalid(read_json(&file), "Epic games")
+            else {
+                continue;
+            };
+            for (app_name, game) in installed {
+                let Some(dir) = game["install_path"].as_str() else {
+                    continue;
+                };
+                candidates.push(Candidate {
+                    name: game["title"].as_str().unwrap_or(&app_name).to_owned(),
+                    dir: dir.into(),
+                });
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+/// Heroic's `gog_store/installed.json`: `{ "installed": [{ "appName", "install_path" }] }`.
+struct Gog;
+impl Source for Gog {
+    fn launcher(&self) -> Launcher {
+        Launcher::Gog
+    }
+    fn candidates(&self) -> Result<Vec<Candidate>> {
+        let Some(config) = dirs::config_dir() else {
+            return Ok(Vec::new());
+        };
+        let Some(installed) = read_json(&config.join("heroic/gog_store/installed.json"))? else {
+            return Ok(Vec::new());
+        };
+
+        let games = installed["installed"]
+            .as_array()
+            .map_or(&[][..], Vec::as_slice);
+        Ok(games
+            .iter()
+            .filter_map(|game| {
+                let dir = PathBuf::from(game["install_path"].as_str()?);
+                Some(Candidate {
+                    name: dir_name(&dir),
+                    dir,
+                })
+            })
+            .collect())
+    }
+}
+
+/// The itch app installs every game into its own folder below `itch/apps`.
+struct Itch;
+impl Source for Itch {
+    fn launcher(&self) -> Launcher {
+        Launcher::Itch
+    }
+    fn candidates(&self) -> Result<Vec<Candidate>> {
+        let Some(config) = dirs::config_dir() else {
+            return Ok(Vec::new());
+        };
+        subdirectories(&config.join("itch/apps"))
+    }
+}
+
+/// Lutris keeps one `<slug>-<timestamp>.yml` per game, whose `exe` lies in the game folder.
+struct Lutris;
+impl Source for Lutris {
+    fn launcher(&self) -> Launcher {
+        Launcher::Lutris
+    }
+    fn candidates(&self) -> Result<Vec<Candidate>> {
+        let folders = [
+            dirs::config_dir().map(|dir| dir.join("lutris/games")),
+            dirs::data_dir().map(|dir| dir.join("lutris/games")),
+        ];
+
+        let mut candidates = Vec::new();
+        for dir in folders.into_iter().flatten() {
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&dir)? {
+                let Some(entry) = skip_invalid(entry, "Lutris game") else {
+                    continue;
+                };
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "yml") {
+                    continue;
+                }
+                let config = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Could not read {}", path.display()));
+                let Some(config) = skip_invalid(config, "Lutris game") else {
+                    continue;
+                };
+                let Some(exe) = config
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("exe:"))
+                else {
+                    continue;
+                };
+                let exe = Path::new(exe.trim().trim_matches(['\'', '"']));
+                let Some(game_dir) = exe.parent() else {
+                    continue;
+                };
+
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let slug = stem
+                    .rsplit_once('-')
+                    .filter(|(_, timestamp)| timestamp.bytes().all(|b| b.is_ascii_digit()))
+                    .map_or(&*stem, |(slug, _)| slug);
+                candidates.push(Candidate {
+                    name: slug.replace('-', " "),
+                    dir: game_dir.to_owned(),
+                });
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+struct Library(Vec<PathBuf>);
+impl Source for Library {
+    fn launcher(&self) -> Launcher {
+        Launcher::Library
+    }
+    fn candidates(&self) -> Result<Vec<Candidate>> {
+        let mut candidates = Vec::new();
+        for folder in &self.0 {
+            let folder = subdirectories(folder)
+                .with_context(|| format!("Could not read {}", folder.display()));
+            candidates.extend(skip_invalid(folder, "library folder").unwrap_or_default());
+        }
+        Ok(candidates)
+    }
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = std::fs::read(path)?;
+    let value =
+        serde_json::from_slice(&data).with_context(|| format!("Invalid {}", path.display()))?;
+    Ok(Some(value))
+}
+
+fn subdirectories(dir: &Path) -> Result<Vec<Candidate>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut candidates = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let Some(entry) = skip_invalid(entry, "directory entry") else {
+            continue;
+        };
+        let path = entry.path();
+        if path.is_dir() {
+            candidates.push(Candidate {
+                name: dir_name(&path),
+                dir: path,
+            });
+        }
+    }
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(candidates)
+}
+
+/// The value of a single entry of a launcher, or `None` after logging why it is skipped.
+fn skip_invalid<T, E: std::fmt::Debug>(entry: Result<T, E>, what: &str) -> Option<T> {
+    match entry {
+        Ok(entry) => Some(entry),
+        Err(e) => {
+            warn!("Skipping invalid {what}: {e:?}");
+            None
+        }
+    }
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| dir.display().to_string())
+}
@@ -1,31 +1,48 @@
 #![windows_subsystem = "windows"]
+mod completion;
+mod config;
+mod discovery;
 mod utils;
 mod widgets;
 mod workers;
 
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
+use masonry::kurbo::Size;
 use masonry::properties::BarColor;
 use masonry::properties::types::Length;
 use masonry::theme::default_property_set;
+use rabex::objects::pptr::PathId;
 use rabex::typetree::NullTypeTreeProvider;
 use rabex_env::Environment;
-use rabex_env::resolver::GameFiles;
-use uniscan::{ScanResults, ScriptFilter, UniScan};
+use uniscan::hierarchy::SceneNode;
+use uniscan::highlight::TokenKind;
+use uniscan::info::{GameInfo, format_size};
+use uniscan::inheritance::EnumNames;
+use uniscan::query::QueryRunner;
+use uniscan::table::Table;
+use uniscan::{FileFilter, ScanResults, ScriptFilter, UniScan};
+use winit::dpi::LogicalSize;
 use winit::error::EventLoopError;
 use xilem::core::one_of::OneOf2;
 use xilem::core::{NoElement, ViewSequence, fork};
 use xilem::style::{Background, BorderColor, BorderWidth, CornerRadius, Padding, Style};
 use xilem::tokio::sync::mpsc::UnboundedSender;
 use xilem::view::{
-    CrossAxisAlignment, FlexExt, MainAxisAlignment, button, flex_col, flex_row, label, portal,
-    prose, sized_box, text_input, virtual_scroll, worker, worker_raw,
+    CrossAxisAlignment, FlexExt, MainAxisAlignment, button, checkbox, flex_col, flex_row, label,
+    portal, prose, resize_observer, sized_box, text_input, virtual_scroll, worker, worker_raw,
 };
-use xilem::{Color, EventLoop, ViewCtx, WidgetView, WindowOptions, Xilem};
+use xilem::{AnyWidgetView, Color, EventLoop, ViewCtx, WidgetView, WindowOptions, Xilem};
 
+use config::{QueryStore, SavedQuery, Session, Settings, UnityGame};
+use discovery::DiscoveredGame;
+use widgets::highlight::{highlighted_query, token_color};
+use widgets::json_tree::{NodeAction, json_tree};
 use widgets::margin;
 use widgets::number_input::{NumberInputState, number_input};
 
@@ -37,6 +54,7 @@ pub const BACKGROUND_COLOR: Color = Color::from_rgb8(18, 18, 20);
 pub const HIGHLIGHT_COLOR: Color = Color::from_rgb8(36, 36, 40);
 pub const BUTTON_COLOR: Color = Color::from_rgb8(60, 90, 140);
 pub const BUTTON_DISABLED_COLOR: Color = Color::from_rgb8(55, 55, 60);
+pub const MATCH_COLOR: Color = Color::from_rgb8(44, 52, 72);
 
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
@@ -46,28 +64,216 @@ enum View {
     Main,
 }
 
-struct UnityGame {
-    name: String,
-    path: PathBuf,
-}
-struct SteamGame {
-    game: UnityGame,
-    app_id: u32,
-}
-
 struct GameSelect {
-    steam_games: Vec<SteamGame>,
+    games: Vec<DiscoveredGame>,
+    /// Whether the games are still being looked for.
+    discovering: bool,
     custom_selection: Option<UnityGame>,
+    /// Info of the game last asked for, `None` while it is being collected.
+    info: Option<(PathBuf, Option<GameInfo>)>,
 
     game_selection: SelectedGame,
 }
 
 struct Main {
     query_raw: String,
+    /// Compile error of `query_raw`, checked before dispatching a rescan.
+    query_error: Option<String>,
     script_filter_raw: String,
     script_filter: ScriptFilter,
     limit: NumberInputState<usize>,
     results: Option<ScanResults>,
+    /// Per result, the tree nodes whose collapsed state was toggled.
+    results_toggled: Vec<HashSet<String>>,
+    results_table: ResultsTable,
+    find: FindBar,
+    /// Number of loose serialized files scanned along with the game.
+    extra_files: usize,
+    files: FileFilterState,
+    enum_names: EnumNames,
+    /// Whether results get the `_base_types` of their script.
+    base_types: bool,
+
+    side_panel: SidePanel,
+    scripts: ScriptBrowser,
+    scene: SceneBrowser,
+    completion: Completion,
+    inspector: Inspector,
+}
+
+/// Narrows the files to scan, see [`FileFilter`].
+#[derive(Default)]
+struct FileFilterState {
+    open: bool,
+    /// Comma separated scene names, as typed.
+    scenes_raw: String,
+    filter: FileFilter,
+}
+
+/// Finds text in the already computed results, without rescanning.
+#[derive(Default)]
+struct FindBar {
+    text: String,
+    regex: bool,
+    /// Show only matching results instead of highlighting them.
+    filter: bool,
+    /// Indices into the results.
+    matches: Vec<usize>,
+    /// Indices into the rows of the sorted results table.
+    table_matches: Vec<usize>,
+    /// Index into the matches of the current view.
+    current: usize,
+    error: Option<String>,
+}
+
+impl FindBar {
+    fn active(&self) -> bool {
+        !self.text.is_empty() && self.error.is_none()
+    }
+
+    /// The rows of a list of `len` items with `matches`. When filtering, rows are the matches.
+    /// When highlighting, row 0 is the current match and the items before it have negative rows,
+    /// so that stepping through the matches scrolls to them without hiding any item.
+    fn rows(&self, matches: &[usize], len: usize) -> Range<i64> {
+        if self.active() && self.filter {
+            return 0..matches.len() as i64;
+        }
+        let offset = self.offset(matches);
+        -offset..len as i64 - offset
+    }
+    /// The item shown at a row, see [`FindBar::rows`].
+    fn item_at(&self, matches: &[usize], row: i64) -> Option<usize> {
+        if self.active() && self.filter {
+            return matches.get(usize::try_from(row).ok()?).copied();
+        }
+        usize::try_from(row + self.offset(matches)).ok()
+    }
+    fn offset(&self, matches: &[usize]) -> i64 {
+        match self.active() {
+            true => matches.get(self.current).map_or(0, |&index| index as i64),
+            false => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ResultsView {
+    /// A table when results are homogeneous flat objects, JSON otherwise.
+    Auto,
+    Json,
+    Table,
+}
+
+struct ResultsTable {
+    view: ResultsView,
+    /// The results as a table, if they are homogeneous flat objects.
+    table: Option<Table>,
+    /// Sorted column and whether it is descending.
+    sort: Option<(String, bool)>,
+    widths: HashMap<String, f64>,
+    hidden: HashSet<String>,
+}
+
+impl ResultsTable {
+    const DEFAULT_WIDTH: f64 = 140.;
+    const MIN_WIDTH: f64 = 40.;
+
+    fn width(&self, column: &str) -> f64 {
+        self.widths
+            .get(column)
+            .copied()
+            .unwrap_or(Self::DEFAULT_WIDTH)
+    }
+
+    fn apply_sort(&mut self) {
+        let (Some(table), Some((column, descending))) = (&mut self.table, &self.sort) else {
+            return;
+        };
+        if let Some(index) = table.columns.iter().position(|c| c == column) {
+            table.sort_by(index, *descending);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SidePanel {
+    Scripts,
+    Scene,
+}
+
+/// Side panel showing the GameObject tree of a build scene.
+#[derive(Default)]
+struct SceneBrowser {
+    scenes: Vec<String>,
+    selected: Option<String>,
+    roots: Vec<SceneNode>,
+    expanded: HashSet<PathId>,
+}
+
+/// Objects opened by following PPtrs, with back/forward history.
+#[derive(Default)]
+struct Inspector {
+    history: Vec<InspectorEntry>,
+    /// Index of the shown entry in `history`.
+    position: usize,
+}
+
+/// Which JSON tree a [`NodeAction`] came from.
+#[derive(Clone, Copy)]
+enum Tree {
+    Result(usize),
+    Inspector,
+}
+
+struct InspectorEntry {
+    title: String,
+    value: uniscan::jaq_json::Val,
+    toggled: HashSet<String>,
+}
+
+impl Inspector {
+    fn current(&self) -> Option<&InspectorEntry> {
+        self.history.get(self.position)
+    }
+    fn push(&mut self, value: uniscan::jaq_json::Val) {
+        let title = match (
+            utils::field_str(&value, "_type"),
+            utils::field_str(&value, "m_Name"),
+        ) {
+            (Some(ty), Some(name)) if !name.is_empty() => format!("{name} ({ty})"),
+            (Some(ty), _) => ty,
+            (None, Some(name)) if !name.is_empty() => name,
+            _ => utils::field_str(&value, "_file").unwrap_or_else(|| "object".to_owned()),
+        };
+        self.history.truncate(self.position + 1);
+        self.history.push(InspectorEntry {
+            title,
+            value,
+            toggled: HashSet::new(),
+        });
+        self.position = self.history.len() - 1;
+    }
+}
+
+/// Candidates offered while typing a query.
+struct Completion {
+    /// Field names of the scripts matched by the current script filter.
+    fields: Vec<String>,
+    builtins: Vec<&'static str>,
+}
+
+/// Side panel listing all scripts found by the stats pass.
+#[derive(Default)]
+struct ScriptBrowser {
+    search: String,
+    scripts: Vec<generic::ScriptStats>,
+}
+
+/// Dropdown for recalling query history and saved queries.
+#[derive(Default)]
+struct QueryMenu {
+    open: bool,
+    save_name: String,
 }
 
 struct App {
@@ -77,6 +283,9 @@ struct App {
     main: Main,
 
     // Shared
+    settings: Settings,
+    queries: QueryStore,
+    query_menu: QueryMenu,
     error: Result<()>,
     progress: Progress,
     sender_rescan: Option<UnboundedSender<rescan::Request>>,
@@ -91,16 +300,40 @@ impl Default for App {
             view: View::GameSelect,
 
             gameselect: GameSelect {
-                steam_games: Vec::new(),
+                games: Vec::new(),
+                discovering: false,
                 custom_selection: None,
+                info: None,
                 game_selection: SelectedGame::None,
             },
             main: Main {
                 query_raw: "".into(),
+                query_error: None,
                 script_filter: ScriptFilter::new(""),
                 script_filter_raw: String::new(),
                 limit: NumberInputState::new(500),
                 results: None,
+                results_toggled: Vec::new(),
+                results_table: ResultsTable {
+                    view: ResultsView::Auto,
+                    table: None,
+                    sort: None,
+                    widths: HashMap::new(),
+                    hidden: HashSet::new(),
+                },
+                find: FindBar::default(),
+                extra_files: 0,
+                files: FileFilterState::default(),
+                enum_names: EnumNames::Off,
+                base_types: false,
+                side_panel: SidePanel::Scripts,
+                scripts: ScriptBrowser::default(),
+                scene: SceneBrowser::default(),
+                inspector: Inspector::default(),
+                completion: Completion {
+                    fields: Vec::new(),
+                    builtins: uniscan::query::builtin_names(),
+                },
             },
 
             error: Result::Ok(()),
@@ -109,26 +342,36 @@ impl Default for App {
             sender_generic: None,
             uniscan: Default::default(),
             uniscan_cancel: Arc::new(AtomicBool::new(false)),
+            settings: Settings::load(),
+            queries: QueryStore::load(),
+            query_menu: QueryMenu::default(),
         }
     }
 }
 
 enum SelectedGame {
     None,
-    Steam(usize),
+    Discovered(usize),
     Custom,
+    /// Index into the recently opened games of the settings.
+    Recent(usize),
 }
 
 // Shared
 impl App {
     fn go_to_main(&mut self, selection: SelectedGame) {
         self.gameselect.game_selection = selection;
+        let game = self.selected_game().clone();
+        self.settings.push_recent(&game);
+        // The game just moved to the front of the recent games
+        if let SelectedGame::Recent(_) = self.gameselect.game_selection {
+            self.gameselect.game_selection = SelectedGame::Recent(0);
+        }
 
         self.view = View::Main;
-        self.clear_error();
-        self.send_command(generic::Request::LoadGame(
-            self.selected_game().path.clone(),
-        ));
+        self.error = self.settings.save();
+        self.restore_session(&game.name);
+        self.send_command(generic::Request::LoadGame(game.path));
     }
     fn go_to_gameselect(&mut self) {
         self.cancel_scan();
@@ -136,6 +379,13 @@ impl App {
         self.view = View::GameSelect;
 
         self.main.results = None;
+        self.main.results_toggled.clear();
+        self.main.scripts = ScriptBrowser::default();
+        self.main.scene = SceneBrowser::default();
+        self.main.inspector = Inspector::default();
+        self.main.completion.fields.clear();
+        self.main.extra_files = 0;
+        self.main.files = FileFilterState::default();
         self.set_script_filter(String::new());
         self.set_query(String::new());
         self.clear_error();
@@ -169,52 +419,344 @@ impl App {
 // View: GameSelect
 impl App {
     pub fn selected_game(&self) -> &UnityGame {
+        self.try_selected_game().expect("no game selected")
+    }
+    /// The selected game, `None` on the game selection before one is chosen.
+    pub fn try_selected_game(&self) -> Option<&UnityGame> {
         match self.gameselect.game_selection {
-            SelectedGame::None => unreachable!(),
-            SelectedGame::Steam(i) => &self.gameselect.steam_games[i].game,
-            SelectedGame::Custom => self.gameselect.custom_selection.as_ref().unwrap(),
+            SelectedGame::None => None,
+            SelectedGame::Discovered(i) => Some(&self.gameselect.games[i].game),
+            SelectedGame::Custom => self.gameselect.custom_selection.as_ref(),
+            SelectedGame::Recent(i) => Some(&self.settings.recent_games[i]),
         }
     }
+    fn remove_recent_game(&mut self, index: usize) {
+        self.settings.remove_recent(index);
+        self.error = self.settings.save();
+    }
+    fn set_reopen_last_game(&mut self, reopen: bool) {
+        self.settings.reopen_last_game = reopen;
+        self.error = self.settings.save();
+    }
     pub fn gameselect_open_custom(&mut self) {
         self.send_command(generic::Request::OpenGame);
     }
+    fn show_game_info(&mut self, path: PathBuf) {
+        if self
+            .gameselect
+            .info
+            .as_ref()
+            .is_some_and(|(shown, _)| *shown == path)
+        {
+            self.gameselect.info = None;
+            return;
+        }
+        self.gameselect.info = Some((path.clone(), None));
+        self.send_command(generic::Request::GameInfo(path));
+    }
+    fn game_info_loaded(&mut self, path: PathBuf, info: GameInfo) {
+        // Only if it wasn't closed or replaced in the meantime
+        if let Some((shown, slot)) = &mut self.gameselect.info
+            && *shown == path
+        {
+            *slot = Some(info);
+        }
+    }
+    fn discover_games(&mut self) {
+        self.gameselect.discovering = true;
+        let library_folders = self.settings.library_folders.clone();
+        self.send_command(generic::Request::DiscoverGames(library_folders));
+    }
+    fn games_discovered(&mut self, games: Vec<DiscoveredGame>) {
+        self.gameselect.discovering = false;
+        // Indices into the games would point at different games
+        if let SelectedGame::Discovered(_) = self.gameselect.game_selection {
+            return;
+        }
+        self.gameselect.games = games;
+    }
+    fn add_library_folder(&mut self) {
+        self.send_command(generic::Request::AddLibraryFolder);
+    }
+    fn library_folder_added(&mut self, folder: PathBuf) {
+        if !self.settings.library_folders.contains(&folder) {
+            self.settings.library_folders.push(folder);
+        }
+        self.error = self.settings.save();
+        self.discover_games();
+    }
+    fn remove_library_folder(&mut self, index: usize) {
+        self.settings.library_folders.remove(index);
+        self.error = self.settings.save();
+        self.discover_games();
+    }
 }
 
 // View: Main
 impl App {
     fn set_query(&mut self, query: String) {
         self.main.query_raw = query;
+        let validated: Result<QueryRunner> = QueryRunner::new(self.effective_query());
+        self.main.query_error = validated.err().map(|e| e.to_string());
         self.reload();
     }
+    fn effective_query(&self) -> &str {
+        match self.main.query_raw.as_str() {
+            "" => ".",
+            other => other,
+        }
+    }
     fn set_script_filter(&mut self, script_filter: String) {
         self.main.script_filter_raw = script_filter;
         let new_filter = ScriptFilter::new(&self.main.script_filter_raw);
         if new_filter != self.main.script_filter {
             self.main.script_filter = new_filter;
+            self.request_fields();
             self.reload();
         }
     }
+    fn complete_query(&mut self, completion: &str) {
+        let query = completion::apply(&self.main.query_raw, completion);
+        self.set_query(query);
+    }
+    fn request_fields(&self) {
+        // The worker filters, `is:` terms need the base types of the game's assemblies
+        let scripts = self
+            .main
+            .scripts
+            .scripts
+            .iter()
+            .map(|script| (script.assembly.clone(), script.full_name.clone()))
+            .collect();
+        self.send_command(generic::Request::Fields(
+            self.main.script_filter.clone(),
+            scripts,
+        ));
+    }
+
+    fn set_script_search(&mut self, search: String) {
+        self.main.scripts.search = search;
+    }
+    fn select_script(&mut self, full_name: String) {
+        self.set_script_filter(format!("={full_name}"));
+    }
+
+    fn set_side_panel(&mut self, side_panel: SidePanel) {
+        self.main.side_panel = side_panel;
+    }
+    fn select_scene(&mut self, scene: String) {
+        self.main.scene.roots.clear();
+        self.main.scene.expanded.clear();
+        self.main.scene.selected = Some(scene.clone());
+        self.send_command(generic::Request::Hierarchy(scene));
+    }
+    fn toggle_scene_node(&mut self, game_object: PathId) {
+        let expanded = &mut self.main.scene.expanded;
+        if !expanded.remove(&game_object) {
+            expanded.insert(game_object);
+        }
+    }
+    fn inspect_scene_node(&mut self, node: SceneNode) {
+        let Some(scene) = self.main.scene.selected.clone() else {
+            return;
+        };
+        self.send_command(generic::Request::SceneNode { scene, node });
+    }
 
     fn cancel_scan(&self) {
         self.uniscan_cancel.store(true, Ordering::Release);
     }
 
     fn reload(&self) {
+        if self.main.query_error.is_some() {
+            return;
+        }
         self.cancel_scan();
         utils::time("send rescan", || {
-            let query = match self.main.query_raw.as_str() {
-                "" => ".".into(),
-                other => other.to_owned(),
-            };
-
             self.send_rescan_command(rescan::Request::Scan {
-                query,
+                query: self.effective_query().to_owned(),
                 script: self.main.script_filter.clone(),
+                files: self.main.files.filter.clone(),
+                enum_names: self.main.enum_names,
+                base_types: self.main.base_types,
                 limit: self.main.limit.last_valid,
             });
         });
     }
 
+    fn set_results(&mut self, results: ScanResults) {
+        self.main.results_toggled = vec![HashSet::new(); results.items.len()];
+        self.main.results_table.table = Table::from_items(&results.items);
+        self.main.results_table.apply_sort();
+        self.main.results = Some(results);
+        self.refresh_find();
+    }
+
+    fn refresh_find(&mut self) {
+        let find = &mut self.main.find;
+        find.current = 0;
+        find.matches.clear();
+        find.table_matches.clear();
+        find.error = None;
+        if find.text.is_empty() {
+            return;
+        }
+        match uniscan::search::Pattern::new(&find.text, find.regex) {
+            Ok(pattern) => {
+                let items = self
+                    .main
+                    .results
+                    .as_ref()
+                    .map_or(&[][..], |scan| &scan.items);
+                find.matches = uniscan::search::find(items, &pattern);
+                if let Some(table) = &self.main.results_table.table {
+                    find.table_matches = uniscan::search::find_rows(&table.rows, &pattern);
+                }
+            }
+            Err(e) => find.error = Some(e.to_string()),
+        }
+    }
+    fn set_find_text(&mut self, text: String) {
+        self.main.find.text = text;
+        self.refresh_find();
+    }
+    fn toggle_find_regex(&mut self) {
+        self.main.find.regex = !self.main.find.regex;
+        self.refresh_find();
+    }
+    fn toggle_file_filter(&mut self) {
+        self.main.files.open = !self.main.files.open;
+    }
+    fn set_file_glob(&mut self, glob: String) {
+        self.main.files.filter.glob = glob;
+        self.reload();
+    }
+    fn set_file_scenes(&mut self, scenes: String) {
+        let before = self.main.files.filter.scenes.clone();
+        self.main.files.filter.set_scenes(&scenes);
+        self.main.files.scenes_raw = scenes;
+        if self.main.files.filter.scenes != before {
+            self.reload();
+        }
+    }
+    fn set_only_addressables(&mut self, only_addressables: bool) {
+        self.main.files.filter.only_addressables = only_addressables;
+        self.reload();
+    }
+    fn set_exclude_bundles(&mut self, exclude_bundles: bool) {
+        self.main.files.filter.exclude_bundles = exclude_bundles;
+        self.reload();
+    }
+    fn cycle_enum_names(&mut self) {
+        self.main.enum_names = match self.main.enum_names {
+            EnumNames::Off => EnumNames::Sibling,
+            EnumNames::Sibling => EnumNames::Replace,
+            EnumNames::Replace => EnumNames::Off,
+        };
+        self.reload();
+    }
+    fn toggle_base_types(&mut self) {
+        self.main.base_types = !self.main.base_types;
+        self.reload();
+    }
+    fn toggle_find_filter(&mut self) {
+        self.main.find.filter = !self.main.find.filter;
+    }
+    /// The matches of the results as currently shown, as JSON or as a table.
+    fn find_matches(&self) -> &[usize] {
+        match self.showing_table() {
+            true => &self.main.find.table_matches,
+            false => &self.main.find.matches,
+        }
+    }
+    fn find_step(&mut self, forward: bool) {
+        let len = self.find_matches().len();
+        let find = &mut self.main.find;
+        if len == 0 {
+            return;
+        }
+        find.current = match forward {
+            true => (find.current + 1) % len,
+            false => (find.current + len - 1) % len,
+        };
+    }
+
+    /// The result shown at a row of the JSON view, see [`FindBar::rows`].
+    fn result_at_row(&self, row: i64) -> Option<usize> {
+        let find = &self.main.find;
+        find.item_at(&find.matches, row)
+    }
+    fn result_rows(&self) -> Range<i64> {
+        let find = &self.main.find;
+        find.rows(&find.matches, self.results().len())
+    }
+
+    fn showing_table(&self) -> bool {
+        let table = &self.main.results_table;
+        table.view != ResultsView::Json && table.table.is_some()
+    }
+    fn set_results_view(&mut self, view: ResultsView) {
+        self.main.results_table.view = view;
+        self.main.find.current = 0;
+    }
+    fn sort_table(&mut self, column: String) {
+        let table = &mut self.main.results_table;
+        table.sort = match table.sort.take() {
+            Some((sorted, descending)) if sorted == column => Some((column, !descending)),
+            _ => Some((column, false)),
+        };
+        table.apply_sort();
+        self.refresh_find();
+    }
+    fn resize_column(&mut self, column: String, delta: f64) {
+        let table = &mut self.main.results_table;
+        let width = (table.width(&column) + delta).max(ResultsTable::MIN_WIDTH);
+        table.widths.insert(column, width);
+    }
+    fn hide_column(&mut self, column: String) {
+        self.main.results_table.hidden.insert(column);
+    }
+    fn show_all_columns(&mut self) {
+        self.main.results_table.hidden.clear();
+    }
+
+    fn toggled_mut(&mut self, tree: Tree) -> Option<&mut HashSet<String>> {
+        match tree {
+            Tree::Result(index) => self.main.results_toggled.get_mut(index),
+            Tree::Inspector => {
+                let inspector = &mut self.main.inspector;
+                let entry = inspector.history.get_mut(inspector.position)?;
+                Some(&mut entry.toggled)
+            }
+        }
+    }
+    fn tree_action(&mut self, tree: Tree, action: NodeAction) {
+        match action {
+            NodeAction::Toggle(path) => {
+                if let Some(toggled) = self.toggled_mut(tree)
+                    && !toggled.remove(&path)
+                {
+                    toggled.insert(path);
+                }
+            }
+            NodeAction::Follow(pptr) => self.send_command(generic::Request::Deref(pptr)),
+            NodeAction::CopyPath(path) => self.error = copy_to_clipboard(path),
+            NodeAction::CopyValue(value) => {
+                self.error = copy_to_clipboard(uniscan::to_pretty_json(&value))
+            }
+        }
+    }
+
+    fn inspector_go_to(&mut self, position: usize) {
+        let inspector = &mut self.main.inspector;
+        if position < inspector.history.len() {
+            inspector.position = position;
+        }
+    }
+    fn inspector_close(&mut self) {
+        self.main.inspector = Inspector::default();
+    }
+
     fn results(&self) -> &[uniscan::jaq_json::Val] {
         match self.main.results {
             Some(ref scan) => scan.items.as_slice(),
@@ -222,6 +764,100 @@ impl App {
         }
     }
 
+    fn restore_session(&mut self, game: &str) {
+        let Some(session) = self.settings.session(game).cloned() else {
+            return;
+        };
+        self.main.limit = NumberInputState::new(session.limit);
+        self.set_script_filter(session.script_filter);
+        self.set_query(session.query);
+    }
+    fn save_session(&mut self) {
+        // Scans of an empty query also finish after going back to the game selection
+        let Some(game) = self.try_selected_game().map(|game| game.name.clone()) else {
+            return;
+        };
+        let session = Session {
+            query: self.main.query_raw.clone(),
+            script_filter: self.main.script_filter_raw.clone(),
+            limit: self.main.limit.last_valid,
+        };
+        if self.settings.session(&game) == Some(&session) {
+            return;
+        }
+        self.settings.sessions.insert(game, session);
+        self.error = self.settings.save();
+    }
+    /// Add the query to the history, on an explicit action rather than on every keystroke.
+    fn record_history(&mut self) {
+        if self.main.query_raw.is_empty() || self.main.query_error.is_some() {
+            return;
+        }
+        let Some(game) = self.try_selected_game().map(|game| game.name.clone()) else {
+            return;
+        };
+        if self.queries.history(&game).first() == Some(&self.main.query_raw) {
+            return;
+        }
+        self.queries.push_history(&game, &self.main.query_raw);
+        self.error = self.queries.save();
+    }
+    fn toggle_query_menu(&mut self) {
+        self.query_menu.open = !self.query_menu.open;
+    }
+    fn set_save_name(&mut self, name: String) {
+        self.query_menu.save_name = name;
+    }
+    fn apply_saved_query(&mut self, saved: SavedQuery) {
+        self.query_menu.open = false;
+        self.main.limit = NumberInputState::new(saved.limit);
+        self.set_script_filter(saved.script_filter);
+        self.set_query(saved.query);
+    }
+    fn save_current_query(&mut self) {
+        let name = match self.query_menu.save_name.trim() {
+            "" => self.main.query_raw.clone(),
+            name => name.to_owned(),
+        };
+        let saved = SavedQuery {
+            name,
+            script_filter: self.main.script_filter_raw.clone(),
+            query: self.main.query_raw.clone(),
+            limit: self.main.limit.last_valid,
+        };
+        let game = self.selected_game().name.clone();
+        self.queries.save_queries(&game, [saved]);
+        self.query_menu.save_name.clear();
+        self.error = self.queries.save();
+        self.record_history();
+    }
+    fn remove_saved_query(&mut self, name: String) {
+        let Some(game) = self.try_selected_game().map(|game| game.name.clone()) else {
+            return;
+        };
+        self.queries.remove_saved(&game, &name);
+        self.error = self.queries.save();
+    }
+    fn import_queries(&mut self) {
+        // The dialog can outlive the selection, so the queries go to the game it was opened for
+        let Some(game) = self.try_selected_game().map(|game| game.name.clone()) else {
+            return;
+        };
+        self.send_command(generic::Request::ImportQueries(game));
+    }
+    fn imported_queries(&mut self, game: String, queries: Vec<SavedQuery>) {
+        self.queries.save_queries(&game, queries);
+        self.error = self.queries.save();
+    }
+    fn export_queries(&mut self) -> Result<()> {
+        let Some(game) = self.try_selected_game() else {
+            return Ok(());
+        };
+        let saved = self.queries.saved(&game.name);
+        self.send_command(generic::Request::Save(serde_json::to_string_pretty(saved)?));
+        Ok(())
+    }
+
     fn export(&mut self) -> Result<()> {
         let results = self.results();
 
@@ -255,64 +891,173 @@ impl App {
         let content = flex_col(content)
             .padding(8.)
             .background_color(BACKGROUND_COLOR);
+        // The root fills the window, so its size is the window's, saved when closing
+        let content = resize_observer(
+            |state: &mut App, size: Size| {
+                state.settings.window_size = Some((size.width, size.height));
+            },
+            content,
+        );
         fork(content, App::workers(Arc::clone(&self.uniscan)))
     }
 
     fn ui_gameselect(&mut self) -> impl WidgetView<App> + use<> {
         let header = label("Select a unity game").text_size(26.);
 
+        let recent = self
+            .settings
+            .recent_games
+            .iter()
+            .enumerate()
+            .map(|(i, game)| {
+                flex_row((
+                    sized_box(
+                        button("Open", move |state: &mut App| {
+                            state.go_to_main(SelectedGame::Recent(i))
+                        })
+                        .padding(4.),
+                    ),
+                    info_button(game.path.clone()),
+                    label(game.name.as_str()),
+                    label(game.path.display().to_string())
+                        .text_color(token_color(TokenKind::Comment)),
+                    button("×", move |state: &mut App| state.remove_recent_game(i)).padding(4.),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let library_folders = self
+            .settings
+            .library_folders
+            .iter()
+            .enumerate()
+            .map(|(i, folder)| {
+                flex_row((
+                    label(folder.display().to_string()).text_color(token_color(TokenKind::Comment)),
+                    button("×", move |state: &mut App| state.remove_library_folder(i)).padding(4.),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        let recent = (!recent.is_empty()).then(|| {
+            flex_col((label("Recent").text_size(16.), recent))
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+        });
+
         flex_col((
-            flex_row(header),
+            flex_row((
+                header,
+                checkbox(
+                    "Reopen last game on start",
+                    self.settings.reopen_last_game,
+                    App::set_reopen_last_game,
+                ),
+            ))
+            .main_axis_alignment(MainAxisAlignment::SpaceBetween),
             self.error_ui(),
-            portal({
-                let items = self
-                    .gameselect
-                    .steam_games
-                    .iter()
-                    .enumerate()
-                    .map(|(i, game)| {
-                        flex_row((
-                            sized_box(
-                                button("Open", move |state: &mut App| {
-                                    state.go_to_main(SelectedGame::Steam(i))
-                                })
-                                .padding(4.),
-                            ),
-                            sized_box(label(game.app_id.to_string())).width(Length::px(60.)),
-                            label(game.game.name.as_str()),
-                        ))
-                    })
-                    .collect::<Vec<_>>();
-                let items_empty = items.is_empty();
-                sized_box(
-                    flex_col((
-                        items,
-                        items_empty.then(|| label("No steam games detected.")),
-                        self.gameselect.custom_selection.as_ref().map(|game| {
+            recent,
+            flex_row((
+                portal({
+                    let items = self
+                        .gameselect
+                        .games
+                        .iter()
+                        .enumerate()
+                        .map(|(i, game)| {
                             flex_row((
                                 sized_box(
                                     button("Open", move |state: &mut App| {
-                                        state.go_to_main(SelectedGame::Custom)
+                                        state.go_to_main(SelectedGame::Discovered(i))
                                     })
                                     .padding(4.),
                                 ),
-                                label(game.name.as_str()),
+                                info_button(game.game.path.clone()),
+                                sized_box(label(game.launcher.label())).width(Length::px(60.)),
+                                label(game.game.name.as_str()),
                             ))
-                        }),
-                        button("Open another", App::gameselect_open_custom).padding(4.),
-                    ))
-                    .cross_axis_alignment(CrossAxisAlignment::Start),
-                )
-                .expand()
-            })
+                        })
+                        .collect::<Vec<_>>();
+                    let items_empty = items.is_empty();
+                    sized_box(
+                        flex_col((
+                            items,
+                            items_empty.then(|| {
+                                label(match self.gameselect.discovering {
+                                    true => "Looking for games...",
+                                    false => "No games detected.",
+                                })
+                            }),
+                            self.gameselect.custom_selection.as_ref().map(|game| {
+                                flex_row((
+                                    sized_box(
+                                        button("Open", move |state: &mut App| {
+                                            state.go_to_main(SelectedGame::Custom)
+                                        })
+                                        .padding(4.),
+                                    ),
+                                    info_button(game.path.clone()),
+                                    label(game.name.as_str()),
+                                ))
+                            }),
+                            flex_row((
+                                button("Open another", App::gameselect_open_custom).padding(4.),
+                                button("Add library folder", App::add_library_folder).padding(4.),
+                            )),
+                            library_folders,
+                        ))
+                        .cross_axis_alignment(CrossAxisAlignment::Start),
+                    )
+                    .expand()
+                })
+                .flex(1.),
+                self.ui_game_info(),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Start)
             .flex(1.),
         ))
         .cross_axis_alignment(CrossAxisAlignment::Fill)
     }
 
+    fn ui_game_info(&self) -> Option<impl WidgetView<App> + use<>> {
+        let (_, info) = self.gameselect.info.as_ref()?;
+        let Some(info) = info else {
+            return Some(
+                sized_box(label("Collecting game info…"))
+                    .width(Length::px(320.))
+                    .boxed(),
+            );
+        };
+
+        let row = |name: &'static str, value: String| {
+            flex_row((sized_box(label(name)).width(Length::px(140.)), label(value)))
+        };
+        let scenes = info
+            .scenes
+            .iter()
+            .map(|scene| label(scene.as_str()).text_color(token_color(TokenKind::Comment)))
+            .collect::<Vec<_>>();
+        Some(
+            sized_box(portal(
+                flex_col((
+                    label(info.name.as_str()).text_size(16.),
+                    row("Unity version", info.unity_version.clone()),
+                    row("Scripting backend", info.backend.to_string()),
+                    row("Serialized files", info.serialized_files.to_string()),
+                    row("Addressable bundles", info.addressable_bundles.to_string()),
+                    row("Data size", format_size(info.data_size)),
+                    row("Scenes", info.scenes.len().to_string()),
+                    scenes,
+                ))
+                .cross_axis_alignment(CrossAxisAlignment::Start),
+            ))
+            .width(Length::px(320.))
+            .boxed(),
+        )
+    }
+
     fn ui_main(&mut self) -> impl WidgetView<App> + use<> {
         let search = flex_row((
             text_input(self.main.query_raw.clone(), App::set_query)
+                .on_enter(|state: &mut App, _| state.record_history())
                 .placeholder(".m_GameObject | deref | .m_Name")
                 .flex(1.),
             sized_box(text_input(
@@ -320,40 +1065,91 @@ impl App {
                 App::set_script_filter,
             ))
             .width(Length::px(180.)),
+            button(
+                match self.main.files.filter.is_empty() {
+                    true => "Files",
+                    false => "Files *",
+                },
+                App::toggle_file_filter,
+            )
+            .background_color(match self.main.files.open {
+                true => BUTTON_COLOR,
+                false => HIGHLIGHT_COLOR,
+            })
+            .padding(4.),
+            button("Queries", App::toggle_query_menu)
+                .background_color(match self.query_menu.open {
+                    true => BUTTON_COLOR,
+                    false => HIGHLIGHT_COLOR,
+                })
+                .padding(4.),
         ));
-        let content = virtual_scroll(
-            0..self.results().len() as i64 + 1,
-            |state: &mut App, index| {
-                let index = index as usize;
-                let results = state.results();
-
-                if index == results.len() {
-                    let missing = match state.main.results {
-                        Some(ref scan) => scan.count.saturating_sub(state.main.limit.last_valid),
-                        None => 0,
-                    };
-                    return label(match missing {
-                        0 => String::new(),
-                        n => format!("... ({n} more)"),
-                    })
-                    .boxed();
-                }
+        let completions = completion::completions(
+            &self.main.query_raw,
+            &self.main.completion.fields,
+            &self.main.completion.builtins,
+        )
+        .into_iter()
+        .map(|candidate| {
+            let candidate = candidate.to_owned();
+            button(candidate.clone(), move |state: &mut App| {
+                state.complete_query(&candidate)
+            })
+            .background_color(HIGHLIGHT_COLOR)
+            .padding(2.)
+        })
+        .collect::<Vec<_>>();
+        let completions = (!completions.is_empty()).then(|| {
+            sized_box(flex_col(completions).cross_axis_alignment(CrossAxisAlignment::Start))
+                .background_color(HIGHLIGHT_COLOR)
+                .padding(4.)
+        });
+        let rows = self.result_rows();
+        let json = virtual_scroll(rows.start..rows.end + 1, |state: &mut App, row| {
+            let results = state.results();
 
-                let Some(value) = results.get(index) else {
-                    return flex_col(()).boxed();
+            if row == state.result_rows().end {
+                let missing = match state.main.results {
+                    Some(ref scan) => scan.count.saturating_sub(state.main.limit.last_valid),
+                    None => 0,
                 };
+                return label(match missing {
+                    0 => String::new(),
+                    n => format!("... ({n} more)"),
+                })
+                .boxed();
+            }
 
-                let val = uniscan::to_pretty_json(value);
+            let Some((index, value)) = state
+                .result_at_row(row)
+                .and_then(|index| Some((index, results.get(index)?)))
+            else {
+                return flex_col(()).boxed();
+            };
+            let find = &state.main.find;
+            let highlighted = find.active() && !find.filter && find.matches.contains(&index);
 
-                margin(
-                    sized_box(prose(val))
-                        .background_color(HIGHLIGHT_COLOR)
-                        .padding(4.),
-                    Padding::bottom(8.),
-                )
-                .boxed()
-            },
-        );
+            let tree = json_tree(
+                value,
+                &state.main.results_toggled[index],
+                move |state: &mut App, action| state.tree_action(Tree::Result(index), action),
+            );
+
+            margin(
+                sized_box(tree)
+                    .background_color(match highlighted {
+                        true => MATCH_COLOR,
+                        false => HIGHLIGHT_COLOR,
+                    })
+                    .padding(4.),
+                Padding::bottom(8.),
+            )
+            .boxed()
+        });
+        let content = match self.showing_table() {
+            true => OneOf2::A(self.ui_table()),
+            false => OneOf2::B(json),
+        };
 
         let can_export = self
             .main
@@ -361,22 +1157,82 @@ impl App {
             .as_ref()
             .is_some_and(|scan| scan.count != 0);
 
-        flex_col((
+        let query_feedback = (!self.main.query_raw.is_empty()).then(|| {
+            flex_col((
+                highlighted_query(&self.main.query_raw),
+                self.main.query_error.as_ref().map(|error| {
+                    prose(error.clone())
+                        .line_break_mode(masonry::properties::LineBreaking::WordWrap)
+                        .text_color(COLOR_ERROR)
+                }),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+        });
+
+        let main = flex_col((
             search,
+            self.main.files.open.then(|| self.ui_file_filter()),
+            self.query_menu.open.then(|| self.ui_query_menu()),
+            query_feedback,
+            completions,
             self.error_ui(),
             self.main.results.as_ref().map(|scan| {
                 let text = format!("Found {} results ({})", scan.count, scan.query_count);
-                label(text)
+                let view = self.main.results_table.view;
+                let view_button = |text: &'static str, target: ResultsView| {
+                    button(text, move |state: &mut App| state.set_results_view(target))
+                        .background_color(match view == target {
+                            true => BUTTON_COLOR,
+                            false => HIGHLIGHT_COLOR,
+                        })
+                        .padding(2.)
+                };
+                flex_row((
+                    label(text).flex(1.),
+                    view_button("Auto", ResultsView::Auto),
+                    view_button("JSON", ResultsView::Json),
+                    view_button("Table", ResultsView::Table)
+                        .disabled(self.main.results_table.table.is_none()),
+                ))
             }),
-            sized_box(content).expand_height().flex(1.0),
+            self.main.results.is_some().then(|| self.ui_find_bar()),
+            flex_row((
+                sized_box(content).expand_height().flex(1.0),
+                self.ui_inspector()
+                    .map(|inspector| sized_box(inspector).width(Length::px(480.))),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Fill)
+            .flex(1.0),
             flex_row((
                 sized_box(button("Back", App::go_to_gameselect)),
+                button(
+                    match self.main.extra_files {
+                        0 => "Add bundles".to_owned(),
+                        n => format!("Add bundles ({n} files)"),
+                    },
+                    |state: &mut App| state.send_command(generic::Request::AddBundles),
+                ),
                 flex_row((self
                     .progress
                     .unfinished()
                     .map(|progress| progress_bar(progress).flex(1.)),))
                 .flex(1.),
                 flex_row((
+                    button(
+                        match self.main.enum_names {
+                            EnumNames::Off => "Enums: numbers",
+                            EnumNames::Sibling => "Enums: numbers + names",
+                            EnumNames::Replace => "Enums: names",
+                        },
+                        App::cycle_enum_names,
+                    ),
+                    button(
+                        match self.main.base_types {
+                            true => "Base types: on",
+                            false => "Base types: off",
+                        },
+                        App::toggle_base_types,
+                    ),
                     label("Limit:"),
                     sized_box(number_input(
                         self.main.limit.clone(),
@@ -401,9 +1257,433 @@ impl App {
             ))
             .main_axis_alignment(MainAxisAlignment::SpaceBetween),
         ))
+        .cross_axis_alignment(CrossAxisAlignment::Fill);
+
+        flex_row((
+            sized_box(self.ui_side_panel()).width(Length::px(260.)),
+            main.flex(1.),
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Fill)
+    }
+
+    fn ui_side_panel(&mut self) -> impl WidgetView<App> + use<> {
+        let current = self.main.side_panel;
+        let tab = move |text: &'static str, panel: SidePanel| {
+            button(text, move |state: &mut App| state.set_side_panel(panel))
+                .background_color(match current == panel {
+                    true => BUTTON_COLOR,
+                    false => HIGHLIGHT_COLOR,
+                })
+                .padding(2.)
+        };
+        let content = match self.main.side_panel {
+            SidePanel::Scripts => OneOf2::A(self.ui_script_browser()),
+            SidePanel::Scene => OneOf2::B(self.ui_scene_browser()),
+        };
+
+        flex_col((
+            flex_row((
+                tab("Scripts", SidePanel::Scripts),
+                tab("Scene", SidePanel::Scene),
+            )),
+            content.flex(1.),
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Fill)
+    }
+
+    fn ui_scene_browser(&mut self) -> impl WidgetView<App> + use<> {
+        let browser = &self.main.scene;
+
+        let scenes = browser
+            .scenes
+            .iter()
+            .map(|scene| {
+                let selected = browser.selected.as_ref() == Some(scene);
+                let scene = scene.clone();
+                button(scene.clone(), move |state: &mut App| {
+                    state.select_scene(scene.clone())
+                })
+                .background_color(match selected {
+                    true => BUTTON_COLOR,
+                    false => HIGHLIGHT_COLOR,
+                })
+                .padding(2.)
+            })
+            .collect::<Vec<_>>();
+
+        fn push_nodes(
+            items: &mut Vec<Box<AnyWidgetView<App>>>,
+            nodes: &[SceneNode],
+            expanded: &HashSet<PathId>,
+            depth: usize,
+        ) {
+            for node in nodes {
+                let is_expanded = expanded.contains(&node.game_object);
+                let game_object = node.game_object;
+                let toggle = (!node.children.is_empty()).then(|| {
+                    button(
+                        if is_expanded { "▾" } else { "▸" },
+                        move |state: &mut App| state.toggle_scene_node(game_object),
+                    )
+                    .padding(1.)
+                });
+                let inspect = {
+                    let node = node.clone();
+                    button(node.name.clone(), move |state: &mut App| {
+                        state.inspect_scene_node(node.clone())
+                    })
+                    .background_color(HIGHLIGHT_COLOR)
+                    .padding(1.)
+                };
+                items.push(
+                    margin(
+                        flex_row((toggle, inspect)),
+                        Padding::left(12. * depth as f64),
+                    )
+                    .boxed(),
+                );
+                if is_expanded {
+                    push_nodes(items, &node.children, expanded, depth + 1);
+                }
+            }
+        }
+        let mut nodes = Vec::new();
+        push_nodes(&mut nodes, &browser.roots, &browser.expanded, 0);
+        let nodes_empty = nodes.is_empty();
+
+        flex_col((
+            portal(flex_col(scenes).cross_axis_alignment(CrossAxisAlignment::Start)),
+            portal(
+                flex_col((
+                    nodes,
+                    (nodes_empty && browser.selected.is_some()).then(|| label("Loading scene...")),
+                ))
+                .cross_axis_alignment(CrossAxisAlignment::Start),
+            )
+            .flex(1.),
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Fill)
+    }
+
+    fn ui_script_browser(&mut self) -> impl WidgetView<App> + use<> {
+        let search = self.main.scripts.search.to_ascii_lowercase();
+        let matching = self.main.scripts.scripts.iter().filter(|script| {
+            search.is_empty() || script.full_name.to_ascii_lowercase().contains(&search)
+        });
+
+        let mut items = Vec::new();
+        let mut current_group: Option<(&str, &str)> = None;
+        for script in matching {
+            let group = (script.assembly.as_str(), script.namespace.as_str());
+            if current_group != Some(group) {
+                if current_group.map(|(assembly, _)| assembly) != Some(group.0) {
+                    items.push(label(script.assembly.as_str()).text_size(16.).boxed());
+                }
+                if !script.namespace.is_empty() {
+                    items.push(margin(label(script.namespace.as_str()), Padding::left(8.)).boxed());
+                }
+                current_group = Some(group);
+            }
+
+            let full_name = script.full_name.clone();
+            let selected = self
+                .main
+                .script_filter_raw
+                .strip_prefix('=')
+                .is_some_and(|filter| filter == script.full_name);
+            let text = format!("{} ({})", script.class, script.count);
+            items.push(
+                margin(
+                    button(text, move |state: &mut App| {
+                        state.select_script(full_name.clone())
+                    })
+                    .background_color(match selected {
+                        true => BUTTON_COLOR,
+                        false => HIGHLIGHT_COLOR,
+                    })
+                    .padding(2.),
+                    Padding::left(16.),
+                )
+                .boxed(),
+            );
+        }
+        let items_empty = items.is_empty();
+
+        flex_col((
+            text_input(self.main.scripts.search.clone(), App::set_script_search)
+                .placeholder("Search scripts"),
+            portal(
+                flex_col((items, items_empty.then(|| label("No scripts found."))))
+                    .cross_axis_alignment(CrossAxisAlignment::Start),
+            )
+            .flex(1.),
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Fill)
+    }
+
+    fn ui_query_menu(&mut self) -> impl WidgetView<App> + use<> {
+        let game = &self.selected_game().name;
+
+        let history = self
+            .queries
+            .history(game)
+            .iter()
+            .map(|query| {
+                let query = query.clone();
+                button(query.clone(), move |state: &mut App| {
+                    state.query_menu.open = false;
+                    state.set_query(query.clone())
+                })
+                .background_color(HIGHLIGHT_COLOR)
+                .padding(2.)
+            })
+            .collect::<Vec<_>>();
+        let saved = self
+            .queries
+            .saved(game)
+            .iter()
+            .map(|saved| {
+                let (apply, name) = (saved.clone(), saved.name.clone());
+                flex_row((
+                    button(saved.name.clone(), move |state: &mut App| {
+                        state.apply_saved_query(apply.clone())
+                    })
+                    .background_color(HIGHLIGHT_COLOR)
+                    .padding(2.),
+                    label(format!("{}  {}", saved.script_filter, saved.query))
+                        .text_color(token_color(TokenKind::Comment)),
+                    button("×", move |state: &mut App| {
+                        state.remove_saved_query(name.clone())
+                    })
+                    .padding(1.),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let (history_empty, saved_empty) = (history.is_empty(), saved.is_empty());
+
+        sized_box(
+            flex_col((
+                label("Saved").text_size(16.),
+                saved,
+                saved_empty.then(|| label("No saved queries.")),
+                flex_row((
+                    text_input(self.query_menu.save_name.clone(), App::set_save_name)
+                        .placeholder("Name")
+                        .flex(1.),
+                    button("Save current", App::save_current_query).padding(4.),
+                    button("Import", App::import_queries).padding(4.),
+                    button("Export", |app: &mut App| app.error = app.export_queries()).padding(4.),
+                )),
+                label("History").text_size(16.),
+                portal(
+                    flex_col((history, history_empty.then(|| label("No history yet."))))
+                        .cross_axis_alignment(CrossAxisAlignment::Start),
+                ),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Fill),
+        )
+        .height(Length::px(320.))
+        .background_color(HIGHLIGHT_COLOR)
+        .padding(4.)
+    }
+
+    fn ui_file_filter(&self) -> impl WidgetView<App> + use<> {
+        let files = &self.main.files;
+        flex_row((
+            text_input(files.filter.glob.clone(), App::set_file_glob)
+                .placeholder("File or bundle name, e.g. *enemies*")
+                .flex(1.),
+            text_input(files.scenes_raw.clone(), App::set_file_scenes)
+                .placeholder("Scenes, comma separated")
+                .flex(1.),
+            checkbox(
+                "Only addressables",
+                files.filter.only_addressables,
+                App::set_only_addressables,
+            ),
+            checkbox(
+                "Exclude bundles",
+                files.filter.exclude_bundles,
+                App::set_exclude_bundles,
+            ),
+        ))
+    }
+
+    fn ui_find_bar(&mut self) -> impl WidgetView<App> + use<> {
+        let find = &self.main.find;
+        let toggle = |text: &'static str, on: bool, f: fn(&mut App)| {
+            button(text, f)
+                .background_color(match on {
+                    true => BUTTON_COLOR,
+                    false => HIGHLIGHT_COLOR,
+                })
+                .padding(2.)
+        };
+        let matches = self.find_matches().len();
+        let status = match (&find.error, matches) {
+            (Some(error), _) => prose(error.clone()).text_color(COLOR_ERROR),
+            _ if find.text.is_empty() => prose(""),
+            (None, 0) => prose("No matches"),
+            (None, n) => prose(format!("{}/{n}", find.current + 1)),
+        };
+        let can_step = find.active() && !find.filter && matches != 0;
+
+        flex_row((
+            text_input(find.text.clone(), App::set_find_text)
+                .placeholder("Find in results")
+                .flex(1.),
+            toggle(".*", find.regex, App::toggle_find_regex),
+            toggle("Filter", find.filter, App::toggle_find_filter),
+            sized_box(status).width(Length::px(90.)),
+            button("↑", |state: &mut App| state.find_step(false)).disabled(!can_step),
+            button("↓", |state: &mut App| state.find_step(true)).disabled(!can_step),
+        ))
+    }
+
+    fn ui_table(&mut self) -> impl WidgetView<App> + use<> {
+        let state = &self.main.results_table;
+        let table = state
+            .table
+            .as_ref()
+            .expect("only shown for tabular results");
+        let visible: Vec<usize> = (0..table.columns.len())
+            .filter(|&i| !state.hidden.contains(&table.columns[i]))
+            .collect();
+
+        let header = visible
+            .iter()
+            .map(|&i| {
+                let column = table.columns[i].clone();
+                let arrow = match &state.sort {
+                    Some((sorted, false)) if *sorted == column => " ▲",
+                    Some((sorted, true)) if *sorted == column => " ▼",
+                    _ => "",
+                };
+                let (sort, shrink, grow, hide) = (
+                    column.clone(),
+                    column.clone(),
+                    column.clone(),
+                    column.clone(),
+                );
+                sized_box(flex_row((
+                    button(format!("{column}{arrow}"), move |state: &mut App| {
+                        state.sort_table(sort.clone())
+                    })
+                    .padding(2.)
+                    .flex(1.),
+                    button("−", move |state: &mut App| {
+                        state.resize_column(shrink.clone(), -20.)
+                    })
+                    .padding(1.),
+                    button("+", move |state: &mut App| {
+                        state.resize_column(grow.clone(), 20.)
+                    })
+                    .padding(1.),
+                    button("×", move |state: &mut App| state.hide_column(hide.clone())).padding(1.),
+                )))
+                .width(Length::px(state.width(&column)))
+            })
+            .collect::<Vec<_>>();
+        let hidden = state.hidden.len();
+
+        let find = &self.main.find;
+        let rows = find.rows(&find.table_matches, table.rows.len());
+        let rows = virtual_scroll(rows, move |app: &mut App, row| {
+            let find = &app.main.find;
+            let state = &app.main.results_table;
+            let Some(index) = find.item_at(&find.table_matches, row) else {
+                return flex_row(()).boxed();
+            };
+            let Some(row) = state.table.as_ref().and_then(|t| t.rows.get(index)) else {
+                return flex_row(()).boxed();
+            };
+            let highlighted = find.active() && !find.filter && find.table_matches.contains(&index);
+            let table = state.table.as_ref().unwrap();
+            let cells = visible
+                .iter()
+                .map(|&i| {
+                    sized_box(label(uniscan::table::cell_text(&row[i])))
+                        .width(Length::px(state.width(&table.columns[i])))
+                })
+                .collect::<Vec<_>>();
+            margin(
+                sized_box(flex_row(cells)).background_color(match highlighted {
+                    true => MATCH_COLOR,
+                    false => BACKGROUND_COLOR,
+                }),
+                Padding::bottom(2.),
+            )
+            .boxed()
+        });
+
+        flex_col((
+            flex_row((
+                header,
+                (hidden > 0).then(|| {
+                    button(
+                        format!("Show all columns ({hidden} hidden)"),
+                        App::show_all_columns,
+                    )
+                    .padding(2.)
+                }),
+            )),
+            rows.flex(1.),
+        ))
         .cross_axis_alignment(CrossAxisAlignment::Fill)
     }
 
+    fn ui_inspector(&mut self) -> Option<impl WidgetView<App> + use<>> {
+        let inspector = &self.main.inspector;
+        let entry = inspector.current()?;
+        let position = inspector.position;
+        let len = inspector.history.len();
+
+        let navigation = flex_row((
+            button("<", move |state: &mut App| {
+                state.inspector_go_to(position.saturating_sub(1))
+            })
+            .disabled(position == 0),
+            button(">", move |state: &mut App| {
+                state.inspector_go_to(position + 1)
+            })
+            .disabled(position + 1 == len),
+            label(entry.title.clone()).flex(1.),
+            button("Close", App::inspector_close),
+        ));
+        let breadcrumbs = inspector
+            .history
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                button(entry.title.clone(), move |state: &mut App| {
+                    state.inspector_go_to(i)
+                })
+                .background_color(match i == position {
+                    true => BUTTON_COLOR,
+                    false => HIGHLIGHT_COLOR,
+                })
+                .padding(2.)
+            })
+            .collect::<Vec<_>>();
+        let tree = json_tree(&entry.value, &entry.toggled, |state: &mut App, action| {
+            state.tree_action(Tree::Inspector, action)
+        });
+
+        Some(
+            flex_col((
+                navigation,
+                portal(flex_row(breadcrumbs)),
+                portal(
+                    sized_box(tree)
+                        .background_color(HIGHLIGHT_COLOR)
+                        .padding(4.),
+                )
+                .flex(1.),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Fill),
+        )
+    }
+
     fn error_ui(&mut self) -> impl WidgetView<App> + use<> {
         self.error
             .as_ref()
@@ -420,17 +1700,34 @@ impl App {
     fn workers(
         uniscan: Arc<Mutex<Option<UniScan>>>,
     ) -> impl ViewSequence<App, (), ViewCtx, NoElement> {
-        // let x = Arc::clone(&self.uniscan);
+        let uniscan_generic = Arc::clone(&uniscan);
         (
             worker(
-                workers::generic::worker,
+                move |a, b| workers::generic::worker(uniscan_generic.clone(), a, b),
                 |state: &mut App, sender| {
                     state.sender_generic = Some(sender);
+                    state.discover_games();
                     auto_select(state);
                 },
                 |state: &mut App, res: Result<generic::Response>| match res {
                     Ok(res) => match res {
                         generic::Response::Noop => {}
+                        generic::Response::ExtraFiles(count) => {
+                            let added = count != state.main.extra_files;
+                            state.main.extra_files = count;
+                            if added {
+                                state.reload();
+                            }
+                        }
+                        generic::Response::Games(games) => state.games_discovered(games),
+                        generic::Response::GameInfo(path, info) => {
+                            state.game_info_loaded(path, info);
+                        }
+                        generic::Response::LibraryFolder(folder) => {
+                            if let Some(folder) = folder {
+                                state.library_folder_added(folder);
+                            }
+                        }
                         generic::Response::OpenAnotherGame(path) => {
                             let Some(path) = path else {
                                 return;
@@ -442,13 +1739,33 @@ impl App {
                             });
                         }
                         generic::Response::Stats(stats) => {
+                            state.main.scripts.scripts = stats.scripts;
                             if state.main.script_filter_raw.is_empty() {
                                 state.set_script_filter(stats.most_used_script);
+                            } else {
+                                state.request_fields();
                             }
                         }
+                        generic::Response::Fields(fields) => {
+                            state.main.completion.fields = fields;
+                        }
+                        generic::Response::ImportedQueries(game, queries) => {
+                            state.imported_queries(game, queries);
+                        }
+                        generic::Response::Dereferenced(value) => {
+                            state.main.inspector.push(value);
+                        }
+                        generic::Response::Hierarchy(roots) => {
+                            state.main.scene.roots = roots;
+                        }
                         generic::Response::Loaded(uniscan) => {
+                            state.main.scene.scenes = uniscan.scene_names.clone();
                             state.uniscan_cancel = Arc::clone(&uniscan.cancel);
                             *state.uniscan.lock().unwrap() = Some(uniscan);
+                            // A restored session doesn't wait for the most used script
+                            if !state.main.script_filter_raw.is_empty() {
+                                state.reload();
+                            }
                         }
                         generic::Response::Progress(progress) => state.progress = progress,
                     },
@@ -462,7 +1779,8 @@ impl App {
                     Ok(res) => match res {
                         rescan::Response::ScanFinished(scan) => {
                             state.clear_error();
-                            state.main.results = Some(scan);
+                            state.set_results(scan);
+                            state.save_session();
                         }
                         rescan::Response::Error(err) => state.set_error(err),
                         rescan::Response::ProgressUpdate(progress) => {
@@ -476,18 +1794,10 @@ impl App {
     }
 }
 
-const AUTOSELECT_GAME: Option<&'static str> = None;
 fn auto_select(state: &mut App) {
-    let Some(game) = AUTOSELECT_GAME else {
-        return;
-    };
-    let index = state
-        .gameselect
-        .steam_games
-        .iter()
-        .position(|x| x.game.name.to_lowercase().contains(game))
-        .unwrap();
-    state.go_to_main(SelectedGame::Steam(index));
+    if state.settings.reopen_last_game && !state.settings.recent_games.is_empty() {
+        state.go_to_main(SelectedGame::Recent(0));
+    }
 }
 
 fn main() -> Result<(), EventLoopError> {
@@ -511,9 +1821,7 @@ fn main() -> Result<(), EventLoopError> {
             .init();
     }
 
-    let mut app = App::default();
-
-    app.set_error_with(|app| Ok(app.gameselect.steam_games = find_games()?));
+    let app = App::default();
 
     let mut properties = default_property_set();
 
@@ -533,37 +1841,30 @@ fn main() -> Result<(), EventLoopError> {
         masonry::theme::ACCENT_COLOR,
     ));
 
-    let app = Xilem::new_simple(app, App::ui, WindowOptions::new("uniscan"))
-        .with_default_properties(properties);
+    let mut window = WindowOptions::new("uniscan").on_close(|state: &mut App| {
+        if let Err(e) = state.settings.save() {
+            tracing::warn!("Failed to save settings: {e}");
+        }
+    });
+    if let Some((width, height)) = app.settings.window_size {
+        window = window.with_initial_inner_size(LogicalSize::new(width, height));
+    }
+
+    let app = Xilem::new_simple(app, App::ui, window).with_default_properties(properties);
     app.run_in(EventLoop::with_user_event())?;
     Ok(())
 }
 
-fn find_games() -> Result<Vec<SteamGame>> {
-    let steam_dir = steamlocate::SteamDir::locate()?;
-
-    let mut results = Vec::new();
-    for lib in steam_dir.libraries()? {
-        let lib = lib?;
-        for app in lib.apps() {
-            let app = app?;
-
-            let Ok(path) = GameFiles::probe_dir(&lib.resolve_app_dir(&app)) else {
-                continue;
-            };
-
-            let game = UnityGame {
-                name: app.name.clone().unwrap_or_else(|| app.install_dir.clone()),
-                path,
-            };
-            results.push(SteamGame {
-                game,
-                app_id: app.app_id,
-            })
-        }
-    }
+fn info_button(path: PathBuf) -> impl WidgetView<App> + use<> {
+    button("Info", move |state: &mut App| {
+        state.show_game_info(path.clone())
+    })
+    .padding(4.)
+}
 
-    Ok(results)
+fn copy_to_clipboard(text: String) -> Result<()> {
+    arboard::Clipboard::new()?.set_text(text)?;
+    Ok(())
 }
 
 fn unity_game_from_path(path: &Path) -> Result<UnityGame> {
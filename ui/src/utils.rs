@@ -1,4 +1,6 @@
 use tracing::info;
+use uniscan::jaq_json::Val;
+use uniscan::jaq_std::ValT as _;
 
 const MIN_LOG_DURATION: std::time::Duration = std::time::Duration::from_millis(1);
 
@@ -11,3 +13,14 @@ pub fn time<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
     }
     res
 }
+
+/// The string value of `key` if `value` is an object containing it.
+pub fn field_str(value: &Val, key: &str) -> Option<String> {
+    let Val::Obj(map) = value else {
+        return None;
+    };
+    let (_, v) = map
+        .iter()
+        .find(|(k, _)| k.as_utf8_bytes() == Some(key.as_bytes()))?;
+    Some(String::from_utf8_lossy(v.as_utf8_bytes()?).into_owned())
+}
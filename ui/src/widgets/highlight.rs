@@ -0,0 +1,28 @@
+use masonry::properties::types::Length;
+use uniscan::highlight::{TokenKind, tokenize};
+use xilem::view::{flex_row, label};
+use xilem::{Color, WidgetView};
+
+pub fn token_color(kind: TokenKind) -> Color {
+    match kind {
+        TokenKind::Keyword => Color::from_rgb8(198, 120, 221),
+        TokenKind::Field => Color::from_rgb8(97, 175, 239),
+        TokenKind::Variable => Color::from_rgb8(224, 108, 117),
+        TokenKind::Ident => Color::from_rgb8(229, 192, 123),
+        TokenKind::String => Color::from_rgb8(152, 195, 121),
+        TokenKind::Number => Color::from_rgb8(209, 154, 102),
+        TokenKind::Comment => Color::from_rgb8(92, 99, 112),
+        TokenKind::Operator | TokenKind::Whitespace => Color::from_rgb8(171, 178, 191),
+    }
+}
+
+/// A read-only line of jq code, colored by token.
+pub fn highlighted_query<State: 'static, Action: 'static>(
+    query: &str,
+) -> impl WidgetView<State, Action> + use<State, Action> {
+    let tokens: Vec<_> = tokenize(query)
+        .into_iter()
+        .map(|(range, kind)| label(query[range].to_owned()).text_color(token_color(kind)))
+        .collect();
+    flex_row(tokens).gap(Length::px(0.))
+}
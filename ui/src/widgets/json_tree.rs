@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use masonry::properties::types::Length;
+use uniscan::highlight::TokenKind;
+use uniscan::jaq_json::Val;
+use uniscan::jaq_std::ValT as _;
+use uniscan::qualify_pptr::QualifiedPPtr;
+use xilem::style::{Padding, Style};
+use xilem::view::{CrossAxisAlignment, button, flex_col, flex_row, label};
+use xilem::{AnyWidgetView, WidgetView};
+
+use super::highlight::token_color;
+use super::margin;
+
+/// Arrays longer than this start out collapsed.
+const COLLAPSE_ARRAYS_OVER: usize = 8;
+
+pub enum NodeAction {
+    /// Flip the collapsed state of the node at the path.
+    Toggle(String),
+    CopyPath(String),
+    CopyValue(Val),
+    /// Open the object a qualified PPtr points to.
+    Follow(Val),
+}
+
+/// An interactive view of a JSON value with collapsible objects and arrays.
+///
+/// `toggled` holds the jq paths (like `.m_Component[0]`) of nodes whose collapsed state differs
+/// from the default, which is expanded except for long arrays.
+pub fn json_tree<State: 'static, F>(
+    value: &Val,
+    toggled: &HashSet<String>,
+    on_action: F,
+) -> Box<AnyWidgetView<State>>
+where
+    F: Fn(&mut State, NodeAction) + Clone + Send + Sync + 'static,
+{
+    node(None, value, String::new(), toggled, on_action)
+}
+
+fn node<State: 'static, F>(
+    key: Option<String>,
+    value: &Val,
+    path: String,
+    toggled: &HashSet<String>,
+    on_action: F,
+) -> Box<AnyWidgetView<State>>
+where
+    F: Fn(&mut State, NodeAction) + Clone + Send + Sync + 'static,
+{
+    let key = key.map(|key| label(format!("{key}:")));
+
+    let actions = {
+        let copy_path = match path.as_str() {
+            "" => ".".to_owned(),
+            path => path.to_owned(),
+        };
+        let copy_value = value.clone();
+        let (on_path, on_value) = (on_action.clone(), on_action.clone());
+        flex_row((
+            small_button("path", move |state: &mut State| {
+                on_path(state, NodeAction::CopyPath(copy_path.clone()))
+            }),
+            small_button("value", move |state: &mut State| {
+                on_value(state, NodeAction::CopyValue(copy_value.clone()))
+            }),
+        ))
+    };
+
+    let children: Vec<(String, String, &Val)> = match value {
+        pptr if QualifiedPPtr::is_qualified(pptr) => {
+            let pptr = pptr.clone();
+            let text = match QualifiedPPtr::from_val(&pptr) {
+                Ok(qualified) => format!("→ {}:{}", qualified.file, qualified.path_id),
+                Err(_) => "→ ?".to_owned(),
+            };
+            let follow = button(text, move |state: &mut State| {
+                on_action(state, NodeAction::Follow(pptr.clone()))
+            })
+            .padding(1.);
+            return flex_row((key, follow, actions)).boxed();
+        }
+        Val::Obj(map) => map
+            .iter()
+            .map(|(k, v)| {
+                let k = String::from_utf8_lossy(k.as_utf8_bytes().unwrap_or_default()).into_owned();
+                (format!("{path}{}", field_path(&k)), k, v)
+            })
+            .collect(),
+        Val::Arr(values) => values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let prefix = if path.is_empty() { "." } else { path.as_str() };
+                (format!("{prefix}[{i}]"), i.to_string(), v)
+            })
+            .collect(),
+        leaf => {
+            let text = uniscan::to_pretty_json(leaf);
+            let kind = match text.as_bytes().first() {
+                Some(b'"') => TokenKind::String,
+                Some(b'n' | b't' | b'f') => TokenKind::Keyword,
+                _ => TokenKind::Number,
+            };
+            let text = label(text).text_color(token_color(kind));
+            return flex_row((key, text, actions)).boxed();
+        }
+    };
+
+    let (open, close) = match value {
+        Val::Arr(_) => ("[", "]"),
+        _ => ("{", "}"),
+    };
+    let default_collapsed = matches!(value, Val::Arr(_)) && children.len() > COLLAPSE_ARRAYS_OVER;
+    let collapsed = default_collapsed != toggled.contains(&path);
+
+    let toggle = {
+        let path = path.clone();
+        let on_action = on_action.clone();
+        small_button(
+            if collapsed { "▸" } else { "▾" },
+            move |state: &mut State| on_action(state, NodeAction::Toggle(path.clone())),
+        )
+    };
+    let badge = label(format!("{open}{}{close}", children.len()))
+        .text_color(token_color(TokenKind::Comment));
+    let header = flex_row((toggle, key, badge, actions));
+
+    if collapsed || children.is_empty() {
+        return header.boxed();
+    }
+
+    let children = children
+        .into_iter()
+        .map(|(child_path, key, child)| {
+            node(Some(key), child, child_path, toggled, on_action.clone())
+        })
+        .collect::<Vec<_>>();
+    flex_col((
+        header,
+        margin(
+            flex_col(children).cross_axis_alignment(CrossAxisAlignment::Start),
+            Padding::left(16.),
+        ),
+    ))
+    .cross_axis_alignment(CrossAxisAlignment::Start)
+    .gap(Length::px(2.))
+    .boxed()
+}
+
+fn small_button<State: 'static>(
+    text: &'static str,
+    on_click: impl Fn(&mut State) + Send + Sync + 'static,
+) -> impl WidgetView<State> + use<State> {
+    button(text, on_click).padding(1.)
+}
+
+/// The jq path segment accessing `key`, quoted unless it is a plain identifier.
+fn field_path(key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match plain {
+        true => format!(".{key}"),
+        false => format!(".{}", jq_string(key)),
+    }
+}
+
+/// `text` as a jq string literal, escaped like JSON.
+fn jq_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
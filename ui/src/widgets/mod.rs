@@ -1,3 +1,5 @@
+pub mod highlight;
+pub mod json_tree;
 pub mod number_input;
 pub mod progress_bar_integer;
 
@@ -1,23 +1,38 @@
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use rabex_env::resolver::EnvResolver as _;
 use rabex_env::unity::types::MonoBehaviour;
 use tracing::warn;
-use uniscan::UniScan;
+use uniscan::hierarchy::SceneNode;
+use uniscan::info::GameInfo;
+use uniscan::jaq_json;
+use uniscan::{ScriptFilter, UniScan};
 use xilem::core::MessageProxy;
 use xilem::tokio;
 use xilem::tokio::sync::mpsc::UnboundedReceiver;
 
+use crate::config::SavedQuery;
+use crate::discovery::{self, DiscoveredGame};
 use crate::widgets::progress_bar_integer::Progress;
 
 pub enum Response {
     Noop,
     OpenAnotherGame(Option<PathBuf>),
+    LibraryFolder(Option<PathBuf>),
+    GameInfo(PathBuf, GameInfo),
+    Games(Vec<DiscoveredGame>),
+    /// The total number of extra serialized files after adding some.
+    ExtraFiles(usize),
     Stats(Stats),
+    Fields(Vec<String>),
+    Dereferenced(jaq_json::Val),
+    /// Queries to save for the named game.
+    ImportedQueries(String, Vec<SavedQuery>),
+    Hierarchy(Vec<SceneNode>),
     Loaded(UniScan),
     Progress(Progress),
 }
@@ -27,7 +42,21 @@ impl std::fmt::Debug for Response {
         match self {
             Self::Noop => write!(f, "Noop"),
             Self::OpenAnotherGame(game) => f.debug_tuple("OpenAnotherGame").field(game).finish(),
+            Self::LibraryFolder(folder) => f.debug_tuple("LibraryFolder").field(folder).finish(),
+            Self::GameInfo(path, info) => {
+                f.debug_tuple("GameInfo").field(path).field(info).finish()
+            }
+            Self::Games(games) => f.debug_tuple("Games").field(&games.len()).finish(),
+            Self::ExtraFiles(count) => f.debug_tuple("ExtraFiles").field(count).finish(),
             Self::Stats(stats) => f.debug_tuple("Stats").field(stats).finish(),
+            Self::Fields(fields) => f.debug_tuple("Fields").field(fields).finish(),
+            Self::Dereferenced(_) => f.debug_tuple("Dereferenced").finish_non_exhaustive(),
+            Self::ImportedQueries(game, queries) => f
+                .debug_tuple("ImportedQueries")
+                .field(game)
+                .field(queries)
+                .finish(),
+            Self::Hierarchy(roots) => f.debug_tuple("Hierarchy").field(&roots.len()).finish(),
             Self::Loaded(_) => f.debug_tuple("Loaded").finish_non_exhaustive(),
             Self::Progress(progress) => f.debug_tuple("Progress").field(&progress).finish(),
         }
@@ -37,21 +66,124 @@ impl std::fmt::Debug for Response {
 #[derive(Debug)]
 pub struct Stats {
     pub most_used_script: String,
+    /// Every script with at least one instance, sorted by assembly, namespace and class.
+    pub scripts: Vec<ScriptStats>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptStats {
+    pub assembly: String,
+    pub namespace: String,
+    pub class: String,
+    pub full_name: String,
+    pub count: usize,
 }
 
 pub enum Request {
     Save(String),
+    /// Pick a JSON file of saved queries, as written by exporting them, to save for the named
+    /// game.
+    ImportQueries(String),
     OpenGame,
+    /// Pick a folder to probe for games.
+    AddLibraryFolder,
     LoadGame(PathBuf),
+    /// Pick loose bundles or `.assets` files to scan along with the loaded game.
+    AddBundles,
+    /// Collect the [`GameInfo`] of a game that isn't necessarily loaded.
+    GameInfo(PathBuf),
+    /// Find the installed games of every launcher and in the given library folders.
+    DiscoverGames(Vec<PathBuf>),
+    /// Collect the typetree field names of the given `(assembly, full_name)` scripts passing the
+    /// filter.
+    Fields(ScriptFilter, Vec<(String, String)>),
+    /// Load the object a qualified PPtr points to.
+    Deref(jaq_json::Val),
+    /// Build the GameObject tree of a build scene.
+    Hierarchy(String),
+    /// Read a GameObject of a scene hierarchy together with its components.
+    SceneNode {
+        scene: String,
+        node: SceneNode,
+    },
 }
 
-pub async fn worker(proxy: MessageProxy<Result<Response>>, mut rx: UnboundedReceiver<Request>) {
+pub async fn worker(
+    uniscan: Arc<Mutex<Option<UniScan>>>,
+    proxy: MessageProxy<Result<Response>>,
+    mut rx: UnboundedReceiver<Request>,
+) {
     while let Some(item) = rx.recv().await {
         let result = match item {
             Request::Save(data) => save(data).await.map(|_| Response::Noop),
+            Request::ImportQueries(game) => import_queries()
+                .await
+                .map(|queries| Response::ImportedQueries(game, queries)),
             Request::OpenGame => open_folder("Open unity game")
                 .await
                 .map(Response::OpenAnotherGame),
+            Request::AddLibraryFolder => open_folder("Add library folder")
+                .await
+                .map(Response::LibraryFolder),
+            Request::AddBundles => add_bundles(Arc::clone(&uniscan))
+                .await
+                .map(Response::ExtraFiles),
+            Request::GameInfo(path) => tokio::task::spawn_blocking(move || {
+                let info = GameInfo::load(&path)?;
+                Ok(Response::GameInfo(path, info))
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .flatten(),
+            Request::DiscoverGames(library_folders) => {
+                tokio::task::spawn_blocking(move || discovery::find_games(&library_folders))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .map(Response::Games)
+            }
+            Request::Fields(script_filter, scripts) => {
+                let uniscan = Arc::clone(&uniscan);
+                tokio::task::spawn_blocking(move || fields(&uniscan, &script_filter, &scripts))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .map(Response::Fields)
+            }
+            Request::Deref(pptr) => {
+                let uniscan = Arc::clone(&uniscan);
+                tokio::task::spawn_blocking(move || {
+                    let uniscan = uniscan.lock().unwrap_or_else(PoisonError::into_inner);
+                    let uniscan = uniscan.as_ref().context("no game loaded")?;
+                    uniscan::query::deref(&uniscan.env, &uniscan.index, pptr)
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .flatten()
+                .map(Response::Dereferenced)
+            }
+            Request::Hierarchy(scene) => {
+                let uniscan = Arc::clone(&uniscan);
+                tokio::task::spawn_blocking(move || {
+                    let uniscan = uniscan.lock().unwrap_or_else(PoisonError::into_inner);
+                    let uniscan = uniscan.as_ref().context("no game loaded")?;
+                    uniscan.scene_hierarchy(&scene)
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .flatten()
+                .map(Response::Hierarchy)
+            }
+            Request::SceneNode { scene, node } => {
+                let uniscan = Arc::clone(&uniscan);
+                tokio::task::spawn_blocking(move || {
+                    let uniscan = uniscan.lock().unwrap_or_else(PoisonError::into_inner);
+                    let uniscan = uniscan.as_ref().context("no game loaded")?;
+                    uniscan.scene_node_value(&scene, &node)
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .flatten()
+                .map(Response::Dereferenced)
+            }
             Request::LoadGame(path) => {
                 let _proxy = proxy.clone();
                 let task = tokio::task::spawn_blocking(move || -> Result<_> {
@@ -68,7 +200,10 @@ pub async fn worker(proxy: MessageProxy<Result<Response>>, mut rx: UnboundedRece
                     _proxy.message(Ok(Response::Loaded(uniscan))).log_error();
                     emit_progress("Reading game files");
 
-                    let result = rabex_env::utils::par_fold_reduce::<HashMap<String, usize>, _>(
+                    let result = rabex_env::utils::par_fold_reduce::<
+                        HashMap<(String, String, String), usize>,
+                        _,
+                    >(
                         env.game_files.serialized_files()?,
                         move |scripts, path| {
                             let file = env.load_serialized(path)?;
@@ -76,19 +211,46 @@ pub async fn worker(proxy: MessageProxy<Result<Response>>, mut rx: UnboundedRece
                                 let Some(script) = mb.mono_script()? else {
                                     continue;
                                 };
-                                *scripts.entry(script.full_name().into_owned()).or_default() += 1;
+                                let key = (
+                                    script.assembly_name().into_owned(),
+                                    script.m_Namespace.clone(),
+                                    script.m_ClassName.clone(),
+                                );
+                                *scripts.entry(key).or_default() += 1;
                             }
                             Ok(())
                         },
                     )?;
-                    let most_used_script = result
+
+                    let mut scripts: Vec<ScriptStats> = result
                         .into_iter()
-                        .max_by_key(|(_, c)| *c)
-                        .map(|(script, _)| script);
+                        .map(|((assembly, namespace, class), count)| ScriptStats {
+                            full_name: match namespace.as_str() {
+                                "" => class.clone(),
+                                namespace => format!("{namespace}.{class}"),
+                            },
+                            assembly,
+                            namespace,
+                            class,
+                            count,
+                        })
+                        .collect();
+                    scripts.sort_by(|a, b| {
+                        (&a.assembly, &a.namespace, &a.class).cmp(&(
+                            &b.assembly,
+                            &b.namespace,
+                            &b.class,
+                        ))
+                    });
+                    let most_used_script = scripts
+                        .iter()
+                        .max_by_key(|script| script.count)
+                        .map(|script| script.full_name.clone());
 
                     _proxy
                         .message(Ok(Response::Stats(Stats {
                             most_used_script: most_used_script.unwrap_or_default(),
+                            scripts,
                         })))
                         .log_error();
 
@@ -106,6 +268,36 @@ pub async fn worker(proxy: MessageProxy<Result<Response>>, mut rx: UnboundedRece
     }
 }
 
+fn fields(
+    uniscan: &Mutex<Option<UniScan>>,
+    script_filter: &ScriptFilter,
+    scripts: &[(String, String)],
+) -> Vec<String> {
+    /// Generating typetrees isn't free, so a broad filter only looks at the first few scripts.
+    const MAX_SCRIPTS: usize = 20;
+
+    let uniscan = uniscan.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(uniscan) = uniscan.as_ref() else {
+        return Vec::new();
+    };
+
+    let matching = scripts.iter().filter(|(assembly, full_name)| {
+        uniscan.script_matches_in(script_filter, assembly, full_name)
+    });
+    let mut fields = Vec::new();
+    for (assembly, full_name) in matching.take(MAX_SCRIPTS) {
+        match uniscan::schema::script_typetree(&uniscan.env, assembly, full_name) {
+            Ok(tt) => fields.extend(uniscan::schema::field_names(
+                &uniscan::schema::typetree_schema(&tt),
+            )),
+            Err(e) => warn!("{e:?}"),
+        }
+    }
+    fields.sort_unstable();
+    fields.dedup();
+    fields
+}
+
 trait LogError {
     fn log_error(self);
 }
@@ -125,6 +317,39 @@ async fn open_folder(title: &str) -> Result<Option<PathBuf>> {
     Ok(file.map(|file| file.path().to_owned()))
 }
 
+async fn add_bundles(uniscan: Arc<Mutex<Option<UniScan>>>) -> Result<usize> {
+    let files = rfd::AsyncFileDialog::new()
+        .set_title("Add bundles")
+        .pick_files()
+        .await
+        .unwrap_or_default();
+    let paths: Vec<PathBuf> = files.iter().map(|file| file.path().to_owned()).collect();
+
+    tokio::task::spawn_blocking(move || {
+        let mut uniscan = uniscan.lock().unwrap_or_else(PoisonError::into_inner);
+        let uniscan = uniscan.as_mut().context("no game loaded")?;
+        for path in paths {
+            uniscan.add_extra_files(&path)?;
+        }
+        Ok(uniscan.extra_files.len())
+    })
+    .await?
+}
+
+async fn import_queries() -> Result<Vec<SavedQuery>> {
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_title("Import saved queries")
+        .add_filter("JSON", &["json"])
+        .pick_file()
+        .await
+    else {
+        return Ok(Vec::new());
+    };
+    let queries = serde_json::from_slice(&file.read().await)
+        .with_context(|| format!("Invalid saved queries in {}", file.file_name()))?;
+    Ok(queries)
+}
+
 async fn save(data: String) -> Result<()> {
     let Some(file) = rfd::AsyncFileDialog::new()
         .add_filter("JSON", &["json"])
@@ -3,7 +3,8 @@ use crate::widgets::progress_bar_integer::Progress;
 use anyhow::Result;
 use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
-use uniscan::{ScanResults, ScriptFilter, UniScan};
+use uniscan::inheritance::EnumNames;
+use uniscan::{FileFilter, ScanResults, ScriptFilter, UniScan};
 use xilem::core::MessageProxy;
 use xilem::tokio::sync::mpsc::UnboundedReceiver;
 use xilem::tokio::time::Instant;
@@ -21,6 +22,9 @@ pub enum Request {
     Scan {
         query: String,
         script: ScriptFilter,
+        files: FileFilter,
+        enum_names: EnumNames,
+        base_types: bool,
         limit: usize,
     },
 }
@@ -42,6 +46,9 @@ pub async fn worker(
             Request::Scan {
                 query,
                 script,
+                files: file_filter,
+                enum_names,
+                base_types,
                 limit,
             } => {
                 let uniscan = Arc::clone(&uniscan);
@@ -52,8 +59,11 @@ pub async fn worker(
                         return Ok(ScanResults::default());
                     };
                     uniscan.query.set_query(&query)?;
+                    uniscan.enum_names = enum_names;
+                    uniscan.with_base_types = base_types;
                     utils::time("rescan", || {
                         let files = utils::time("collect files", || uniscan.collect_files())?;
+                        let files = uniscan.filter_files(files, &file_filter);
                         let total = files.len();
 
                         let start = Instant::now();